@@ -0,0 +1,128 @@
+//! The BigInt-only representation which `Digits` replaced, kept so that its
+//! performance can be compared against.
+//!
+//! Only what the benchmarks use is implemented.
+
+use std::cmp::Ordering;
+use std::ops::{Add, Mul};
+
+use num_bigint::BigInt;
+use num_traits::pow;
+
+#[derive(Debug, Eq, Clone)]
+pub struct Decimal {
+    digits: BigInt,
+    decimal_index: usize,
+}
+
+impl Decimal {
+    fn new(digits: BigInt, decimal_index: usize) -> Decimal {
+        let mut value = Decimal {
+            digits,
+            decimal_index,
+        };
+        value.reduce();
+        value
+    }
+
+    pub fn try_from(input: &str) -> Option<Decimal> {
+        let input = input.trim();
+        let mut digits = String::with_capacity(input.len());
+        let mut decimal_index = None;
+        for ch in input.chars() {
+            match ch {
+                '0'..='9' | '-' | '+' => {
+                    digits.push(ch);
+                    if let Some(idx) = decimal_index.as_mut() {
+                        *idx += 1;
+                    }
+                }
+                '.' => {
+                    if decimal_index.is_some() {
+                        return None;
+                    }
+                    decimal_index = Some(0)
+                }
+                _ => return None,
+            }
+        }
+        Some(Decimal::new(
+            digits.parse::<BigInt>().ok()?,
+            decimal_index.unwrap_or_default(),
+        ))
+    }
+
+    /// Add precision to the less-precise value until precisions match
+    fn equalize_precision(one: &mut Decimal, two: &mut Decimal) {
+        fn expand(lower_precision: &mut Decimal, higher_precision: &Decimal) {
+            let precision_difference =
+                higher_precision.decimal_index - lower_precision.decimal_index;
+            lower_precision.digits =
+                &lower_precision.digits * pow(BigInt::from(10_usize), precision_difference);
+            lower_precision.decimal_index += precision_difference;
+        }
+        if one.decimal_index < two.decimal_index {
+            expand(one, two)
+        } else if one.decimal_index > two.decimal_index {
+            expand(two, one)
+        }
+    }
+
+    /// Eliminate extraneous trailing zeroes, by way of a decimal string
+    fn reduce(&mut self) {
+        let extra_zeroes = self
+            .digits
+            .to_string()
+            .chars()
+            .rev()
+            .take(self.decimal_index)
+            .take_while(|&c| c == '0')
+            .count();
+        self.digits = &self.digits / pow(BigInt::from(10_usize), extra_zeroes);
+        self.decimal_index -= extra_zeroes;
+    }
+}
+
+impl Add for Decimal {
+    type Output = Self;
+    fn add(mut self, mut rhs: Self) -> Self {
+        Decimal::equalize_precision(&mut self, &mut rhs);
+        Decimal::new(self.digits + rhs.digits, self.decimal_index)
+    }
+}
+
+impl Mul for Decimal {
+    type Output = Self;
+    fn mul(mut self, mut rhs: Self) -> Self {
+        Decimal::equalize_precision(&mut self, &mut rhs);
+        Decimal::new(
+            self.digits * rhs.digits,
+            self.decimal_index + rhs.decimal_index,
+        )
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Decimal) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Decimal) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Decimal) -> Ordering {
+        if self.decimal_index == other.decimal_index {
+            self.digits.cmp(&other.digits)
+        } else {
+            let mut one = self.clone();
+            let mut two = other.clone();
+            Decimal::equalize_precision(&mut one, &mut two);
+            one.digits.cmp(&two.digits)
+        }
+    }
+}
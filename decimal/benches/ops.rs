@@ -0,0 +1,157 @@
+#[macro_use]
+extern crate criterion;
+extern crate decimal;
+extern crate num_bigint;
+extern crate num_traits;
+
+mod baseline;
+
+use std::ops::{Add, Mul};
+
+use criterion::{black_box, Bencher, Criterion};
+
+/// The operations benchmarked, so that `Decimal` can be compared against the baseline.
+trait Number: Clone + Ord + Add<Output = Self> + Mul<Output = Self> {
+    fn parse(input: &str) -> Self;
+}
+
+impl Number for decimal::Decimal {
+    fn parse(input: &str) -> Self {
+        decimal::Decimal::try_from(input).unwrap()
+    }
+}
+
+impl Number for baseline::Decimal {
+    fn parse(input: &str) -> Self {
+        baseline::Decimal::try_from(input).unwrap()
+    }
+}
+
+/// Benchmark an operation on both `Decimal` and the BigInt-only baseline.
+fn compare<I: ?Sized, F, G>(c: &mut Criterion, name: &str, input: &I, decimal: F, baseline: G)
+where
+    F: Fn(&mut Bencher, &I),
+    G: Fn(&mut Bencher, &I),
+{
+    let mut group = c.benchmark_group(name);
+    group.bench_function("Decimal", |b| decimal(b, input));
+    group.bench_function("BigInt baseline", |b| baseline(b, input));
+    group.finish();
+}
+
+/// Ledger-sized amounts, with two decimal places.
+fn amounts() -> Vec<String> {
+    (0..1000)
+        .map(|i| format!("{}.{:02}", i * 37 % 5000, i % 100))
+        .collect()
+}
+
+fn parse_all<N: Number>(inputs: &[String]) -> Vec<N> {
+    inputs.iter().map(|input| N::parse(input)).collect()
+}
+
+/// Summing many small amounts.
+fn sum<N: Number>(b: &mut Bencher, inputs: &[String]) {
+    let amounts = parse_all::<N>(inputs);
+    let zero = N::parse("0");
+    b.iter(|| {
+        amounts
+            .iter()
+            .fold(zero.clone(), |sum, amount| sum + amount.clone())
+    })
+}
+
+fn sum_small(c: &mut Criterion) {
+    compare(
+        c,
+        "sum small",
+        &amounts()[..],
+        sum::<decimal::Decimal>,
+        sum::<baseline::Decimal>,
+    );
+}
+
+/// Multiplying small amounts by a rate.
+fn mul<N: Number>(b: &mut Bencher, inputs: &[String]) {
+    let amounts = parse_all::<N>(inputs);
+    let rate = N::parse("1.0825");
+    b.iter(|| {
+        amounts.iter().for_each(|amount| {
+            black_box(amount.clone() * rate.clone());
+        })
+    })
+}
+
+fn mul_small(c: &mut Criterion) {
+    compare(
+        c,
+        "mul small",
+        &amounts()[..],
+        mul::<decimal::Decimal>,
+        mul::<baseline::Decimal>,
+    );
+}
+
+/// Comparing small amounts of differing scales.
+fn cmp<N: Number>(b: &mut Bencher, inputs: &[String]) {
+    let amounts = parse_all::<N>(inputs);
+    let threshold = N::parse("2500.5");
+    b.iter(|| amounts.iter().filter(|amount| **amount > threshold).count())
+}
+
+fn cmp_small(c: &mut Criterion) {
+    compare(
+        c,
+        "cmp small",
+        &amounts()[..],
+        cmp::<decimal::Decimal>,
+        cmp::<baseline::Decimal>,
+    );
+}
+
+/// Parsing small amounts.
+fn parse<N: Number>(b: &mut Bencher, inputs: &[String]) {
+    b.iter(|| {
+        inputs.iter().for_each(|input| {
+            black_box(N::parse(input));
+        })
+    })
+}
+
+fn parse_small(c: &mut Criterion) {
+    compare(
+        c,
+        "parse small",
+        &amounts()[..],
+        parse::<decimal::Decimal>,
+        parse::<baseline::Decimal>,
+    );
+}
+
+/// Summing values too large for any fixed-size representation.
+fn big<N: Number>(b: &mut Bencher, input: &str) {
+    let big = N::parse(input);
+    b.iter(|| (0..100).fold(big.clone(), |sum, _| sum + big.clone()))
+}
+
+fn sum_big(c: &mut Criterion) {
+    let input =
+        "100000000000000000000000000000000000000000000.00000000000000000000000000000000000000001";
+    compare(
+        c,
+        "sum big",
+        input,
+        big::<decimal::Decimal>,
+        big::<baseline::Decimal>,
+    );
+}
+
+criterion_group!(
+    benches,
+    sum_small,
+    mul_small,
+    cmp_small,
+    parse_small,
+    sum_big
+);
+criterion_main!(benches);
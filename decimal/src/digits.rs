@@ -0,0 +1,235 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Add, Mul, Neg, Sub};
+
+use num_bigint::{BigInt, Sign};
+use num_traits::{pow, Signed};
+
+/// The digits of a decimal, as an integer
+///
+/// Values which fit in an `i128` are stored inline, avoiding allocation; only values
+/// which don't are stored as a `BigInt`. Arithmetic on small values is promoted to a
+/// `BigInt` only if it overflows.
+#[derive(Debug, Clone)]
+pub(crate) enum Digits {
+    Small(i128),
+    /// Never holds a value which would fit in `Small`
+    Big(BigInt),
+}
+
+impl Digits {
+    pub(crate) fn to_bigint(&self) -> BigInt {
+        match *self {
+            Digits::Small(value) => bigint_from_i128(value),
+            Digits::Big(ref value) => value.clone(),
+        }
+    }
+
+    fn into_bigint(self) -> BigInt {
+        match self {
+            Digits::Small(value) => bigint_from_i128(value),
+            Digits::Big(value) => value,
+        }
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        match *self {
+            Digits::Small(value) => value == 0,
+            Digits::Big(_) => false,
+        }
+    }
+
+    pub(crate) fn is_negative(&self) -> bool {
+        match *self {
+            Digits::Small(value) => value < 0,
+            Digits::Big(ref value) => value.is_negative(),
+        }
+    }
+
+    /// Multiply by `10^exponent`
+    pub(crate) fn scale_up(self, exponent: usize) -> Digits {
+        if let Digits::Small(value) = self {
+            let scaled = checked_pow10(exponent).and_then(|factor| value.checked_mul(factor));
+            if let Some(scaled) = scaled {
+                return Digits::Small(scaled);
+            }
+        }
+        Digits::from(self.into_bigint() * pow(BigInt::from(10_usize), exponent))
+    }
+
+    /// Divide out up to `limit` trailing zeroes, returning how many there were
+    pub(crate) fn strip_trailing_zeroes(&mut self, limit: usize) -> usize {
+        let mut stripped = 0;
+        match *self {
+            Digits::Small(ref mut value) => {
+                if *value == 0 {
+                    return limit;
+                }
+                while stripped < limit && *value % 10 == 0 {
+                    *value /= 10;
+                    stripped += 1;
+                }
+            }
+            Digits::Big(ref mut value) => {
+                let ten = BigInt::from(10_usize);
+                while stripped < limit {
+                    let quotient = &*value / &ten;
+                    if &quotient * &ten != *value {
+                        break;
+                    }
+                    *value = quotient;
+                    stripped += 1;
+                }
+                if stripped > 0 {
+                    *self = Digits::from(self.to_bigint());
+                }
+            }
+        }
+        stripped
+    }
+
+    /// Apply an operation, using `small` if both operands are small and it doesn't
+    /// overflow, and `big` otherwise
+    fn combine(
+        self,
+        other: Digits,
+        small: fn(i128, i128) -> Option<i128>,
+        big: fn(BigInt, BigInt) -> BigInt,
+    ) -> Digits {
+        if let (&Digits::Small(a), &Digits::Small(b)) = (&self, &other) {
+            if let Some(value) = small(a, b) {
+                return Digits::Small(value);
+            }
+        }
+        Digits::from(big(self.into_bigint(), other.into_bigint()))
+    }
+}
+
+/// `10^exponent`, if it fits in an `i128`
+fn checked_pow10(exponent: usize) -> Option<i128> {
+    if exponent > u32::MAX as usize {
+        return None;
+    }
+    10_i128.checked_pow(exponent as u32)
+}
+
+/// `BigInt` doesn't convert from 128-bit integers, so build them from bytes
+pub(crate) fn bigint_from_u128(value: u128) -> BigInt {
+    BigInt::from_bytes_le(Sign::Plus, &value.to_le_bytes())
+}
+
+fn bigint_from_i128(value: i128) -> BigInt {
+    let sign = if value < 0 { Sign::Minus } else { Sign::Plus };
+    BigInt::from_bytes_le(sign, &value.unsigned_abs().to_le_bytes())
+}
+
+fn bigint_to_i128(value: &BigInt) -> Option<i128> {
+    let (sign, bytes) = value.to_bytes_le();
+    if bytes.len() > 16 {
+        return None;
+    }
+    let mut buffer = [0; 16];
+    buffer[..bytes.len()].copy_from_slice(&bytes);
+    let magnitude = u128::from_le_bytes(buffer);
+    if sign == Sign::Minus {
+        0_i128.checked_sub_unsigned(magnitude)
+    } else {
+        i128::try_from(magnitude).ok()
+    }
+}
+
+impl From<i128> for Digits {
+    fn from(value: i128) -> Digits {
+        Digits::Small(value)
+    }
+}
+
+impl From<BigInt> for Digits {
+    fn from(value: BigInt) -> Digits {
+        match bigint_to_i128(&value) {
+            Some(value) => Digits::Small(value),
+            None => Digits::Big(value),
+        }
+    }
+}
+
+impl Add for Digits {
+    type Output = Digits;
+    fn add(self, other: Digits) -> Digits {
+        self.combine(other, i128::checked_add, |a, b| a + b)
+    }
+}
+
+impl Sub for Digits {
+    type Output = Digits;
+    fn sub(self, other: Digits) -> Digits {
+        self.combine(other, i128::checked_sub, |a, b| a - b)
+    }
+}
+
+impl Mul for Digits {
+    type Output = Digits;
+    fn mul(self, other: Digits) -> Digits {
+        self.combine(other, i128::checked_mul, |a, b| a * b)
+    }
+}
+
+impl Neg for Digits {
+    type Output = Digits;
+    fn neg(self) -> Digits {
+        match self {
+            // only `i128::MIN` can't be negated
+            Digits::Small(value) => match value.checked_neg() {
+                Some(negated) => Digits::Small(negated),
+                None => Digits::Big(-bigint_from_i128(value)),
+            },
+            // negating `-i128::MIN` produces `i128::MIN`
+            Digits::Big(value) => Digits::from(-value),
+        }
+    }
+}
+
+impl PartialEq for Digits {
+    fn eq(&self, other: &Digits) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Digits {}
+
+impl PartialOrd for Digits {
+    fn partial_cmp(&self, other: &Digits) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Digits {
+    fn cmp(&self, other: &Digits) -> Ordering {
+        match (self, other) {
+            (Digits::Small(a), Digits::Small(b)) => a.cmp(b),
+            (Digits::Big(a), Digits::Big(b)) => a.cmp(b),
+            _ => self.to_bigint().cmp(&other.to_bigint()),
+        }
+    }
+}
+
+// equal values are always stored the same way, so they always hash the same way
+impl Hash for Digits {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match *self {
+            Digits::Small(ref value) => value.hash(state),
+            Digits::Big(ref value) => value.hash(state),
+        }
+    }
+}
+
+impl fmt::Display for Digits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Digits::Small(ref value) => fmt::Display::fmt(value, f),
+            Digits::Big(ref value) => fmt::Display::fmt(value, f),
+        }
+    }
+}
@@ -1,84 +1,218 @@
 use std::cmp::Ordering;
+use std::convert::{TryFrom, TryInto};
+use std::error::Error;
 use std::fmt;
-use std::ops::{Add, Sub, Mul};
-
-#[macro_use]
-extern crate try_opt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::iter::{Product, Sum};
+use std::mem;
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign,
+};
 
 extern crate num_bigint;
 use num_bigint::BigInt;
 extern crate num_traits;
-use num_traits::pow;
+use num_traits::{pow, One, Signed, Zero};
+
+#[cfg(feature = "serde")]
+extern crate serde;
+
+mod digits;
+use digits::{bigint_from_u128, Digits};
+
+#[cfg(feature = "serde")]
+pub mod serialization;
+
+/// How to round a value which can't be represented exactly at the desired scale
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Round to the nearest value; if equidistant, to the one whose last digit is even
+    HalfEven,
+    /// Round to the nearest value; if equidistant, away from zero
+    HalfUp,
+    /// Round toward zero
+    Down,
+    /// Round away from zero
+    Up,
+    /// Round toward positive infinity
+    Ceiling,
+    /// Round toward negative infinity
+    Floor,
+}
+
+impl RoundingMode {
+    /// Divide one integer by another, rounding the quotient to an integer
+    fn divide(self, numerator: &BigInt, denominator: &BigInt) -> BigInt {
+        let quotient = numerator / denominator;
+        let remainder = numerator - &quotient * denominator;
+        if remainder.is_zero() {
+            return quotient;
+        }
+
+        // the exact quotient lies strictly between `quotient` and `away`
+        let positive = numerator.is_negative() == denominator.is_negative();
+        let away = if positive {
+            &quotient + BigInt::one()
+        } else {
+            &quotient - BigInt::one()
+        };
+        let halfway = (remainder.abs() * BigInt::from(2)).cmp(&denominator.abs());
+        let round_away = match self {
+            RoundingMode::HalfEven => match halfway {
+                Ordering::Less => false,
+                Ordering::Equal => !(&quotient % BigInt::from(2)).is_zero(),
+                Ordering::Greater => true,
+            },
+            RoundingMode::HalfUp => halfway != Ordering::Less,
+            RoundingMode::Down => false,
+            RoundingMode::Up => true,
+            RoundingMode::Ceiling => positive,
+            RoundingMode::Floor => !positive,
+        };
+        if round_away {
+            away
+        } else {
+            quotient
+        }
+    }
+}
+
+/// The precision with which to compute quotients
+///
+/// Quotients are rounded to `scale` digits after the decimal point, according to `rounding`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Context {
+    pub scale: usize,
+    pub rounding: RoundingMode,
+}
+
+impl Context {
+    pub fn new(scale: usize, rounding: RoundingMode) -> Context {
+        Context { scale, rounding }
+    }
+}
+
+/// Twenty digits after the decimal point, rounding half to even
+impl Default for Context {
+    fn default() -> Context {
+        Context::new(20, RoundingMode::HalfEven)
+    }
+}
 
 /// Type implementing arbitrary-precision decimal arithmetic
 #[derive(Debug, Eq, Clone)]
 pub struct Decimal {
-    digits: BigInt,
+    digits: Digits,
     decimal_index: usize,
 }
 
 impl Decimal {
-    fn new(digits: BigInt, decimal_index: usize) -> Decimal {
+    fn new<D: Into<Digits>>(digits: D, decimal_index: usize) -> Decimal {
         let mut value = Decimal {
-            digits: digits,
-            decimal_index: decimal_index,
+            digits: digits.into(),
+            decimal_index,
         };
         value.reduce();
         value
     }
 
-    pub fn try_from(mut input: &str) -> Option<Decimal> {
-        // clear extraneous whitespace
-        input = input.trim();
+    /// Parse a decimal, returning `None` if it's invalid
+    ///
+    /// See the `FromStr` implementation for details of the accepted format.
+    ///
+    /// This takes precedence over `TryFrom::try_from`, so use `try_into` to convert
+    /// from other numeric types.
+    pub fn try_from(input: &str) -> Option<Decimal> {
+        input.parse().ok()
+    }
 
-        // don't bother to trim extraneous zeroes
-        // leave it to users to manage their own memory
+    /// The absolute value
+    pub fn abs(&self) -> Decimal {
+        if self.digits.is_negative() {
+            -self
+        } else {
+            self.clone()
+        }
+    }
 
-        // now build a representation of the number to parse
-        let mut digits = String::with_capacity(input.len());
-        let mut decimal_index = None;
-        for ch in input.chars() {
-            match ch {
-                '0'...'9' | '-' | '+' => {
-                    digits.push(ch);
-                    if let Some(idx) = decimal_index.as_mut() {
-                        *idx += 1;
-                    }
-                }
-                '.' => {
-                    if decimal_index.is_some() {
-                        return None;
-                    }
-                    decimal_index = Some(0)
-                }
-                _ => return None,
-            }
+    /// `-1` if the value is negative, `0` if it's zero, and `1` if it's positive
+    pub fn signum(&self) -> Decimal {
+        if self.digits.is_negative() {
+            Decimal::from(-1)
+        } else if self.digits.is_zero() {
+            Decimal::from(0)
+        } else {
+            Decimal::from(1)
+        }
+    }
+
+    /// Round to at most `scale` digits after the decimal point
+    pub fn round(&self, scale: usize, mode: RoundingMode) -> Decimal {
+        if scale >= self.decimal_index {
+            return self.clone();
         }
+        let divisor = pow(BigInt::from(10_usize), self.decimal_index - scale);
+        Decimal::new(mode.divide(&self.digits.to_bigint(), &divisor), scale)
+    }
+
+    /// Divide by `rhs`, rounding the quotient as specified by `context`
+    ///
+    /// Returns `None` if `rhs` is zero.
+    pub fn div_with(&self, rhs: &Decimal, context: Context) -> Option<Decimal> {
+        if rhs.digits.is_zero() {
+            return None;
+        }
+        // (a / 10^i) / (b / 10^j) * 10^scale == (a * 10^(j + scale)) / (b * 10^i)
+        let numerator = self.digits.to_bigint()
+            * pow(BigInt::from(10_usize), rhs.decimal_index + context.scale);
+        let denominator =
+            rhs.digits.to_bigint() * pow(BigInt::from(10_usize), self.decimal_index);
         Some(Decimal::new(
-            try_opt!(digits.parse::<BigInt>().ok()),
-            match decimal_index {
-                Some(idx) => idx,
-                None => 0,
-            },
+            context.rounding.divide(&numerator, &denominator),
+            context.scale,
+        ))
+    }
+
+    /// Compute the remainder of dividing by `rhs`
+    ///
+    /// The quotient is rounded to an integer according to `rounding`, which determines
+    /// the sign of the remainder: `Down` produces a remainder with the sign of `self`,
+    /// like Rust's `%` on integers, while `Floor` produces one with the sign of `rhs`.
+    /// The remainder itself is always exact.
+    ///
+    /// Returns `None` if `rhs` is zero.
+    pub fn rem_with(&self, rhs: &Decimal, rounding: RoundingMode) -> Option<Decimal> {
+        if rhs.digits.is_zero() {
+            return None;
+        }
+        let mut one = self.clone();
+        let mut two = rhs.clone();
+        Decimal::equalize_precision(&mut one, &mut two);
+        let (one_digits, two_digits) = (one.digits.to_bigint(), two.digits.to_bigint());
+        let quotient = rounding.divide(&one_digits, &two_digits);
+        Some(Decimal::new(
+            one_digits - quotient * two_digits,
+            one.decimal_index,
         ))
     }
 
     /// Add precision to the less-precise value until precisions match
     ///
     /// Precision, in this case, is defined as the decimal index.
-    fn equalize_precision(mut one: &mut Decimal, mut two: &mut Decimal) {
+    fn equalize_precision(one: &mut Decimal, two: &mut Decimal) {
         fn expand(lower_precision: &mut Decimal, higher_precision: &Decimal) {
             let precision_difference =
-                (higher_precision.decimal_index - lower_precision.decimal_index) as usize;
+                higher_precision.decimal_index - lower_precision.decimal_index;
 
-            lower_precision.digits = &lower_precision.digits *
-                pow(BigInt::from(10_usize), precision_difference);
+            let digits = std::mem::replace(&mut lower_precision.digits, Digits::Small(0));
+            lower_precision.digits = digits.scale_up(precision_difference);
             lower_precision.decimal_index += precision_difference;
         }
         if one.decimal_index < two.decimal_index {
-            expand(&mut one, &two)
+            expand(one, two)
         } else if one.decimal_index > two.decimal_index {
-            expand(&mut two, &one)
+            expand(two, one)
         }
         assert_eq!(one.decimal_index, two.decimal_index);
     }
@@ -87,15 +221,8 @@ impl Decimal {
     ///
     /// This reduces the decimal index, so that the raw values are easier to parse
     fn reduce(&mut self) {
-        let extra_zeroes = self.digits
-            .to_string() // produce a decimal representation
-            .chars()
-            .rev() // trailing values
-            .take(self.decimal_index) // not counting past the decimal point
-            .take_while(|&c| c == '0') // counting only `0` digits
-            .count();
-        self.digits = &self.digits / pow(BigInt::from(10_usize), extra_zeroes);
-        self.decimal_index -= extra_zeroes;
+        // not counting past the decimal point
+        self.decimal_index -= self.digits.strip_trailing_zeroes(self.decimal_index);
     }
 }
 
@@ -116,7 +243,416 @@ macro_rules! auto_impl_decimal_ops {
 
 auto_impl_decimal_ops!(Add, add, |s, o| s + o, |s, _| s);
 auto_impl_decimal_ops!(Sub, sub, |s, o| s - o, |s, _| s);
-auto_impl_decimal_ops!(Mul, mul, |s, o| s * o, |s, o| s + o);
+
+// multiplication doesn't need the precisions to match
+impl Mul for Decimal {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Decimal::new(self.digits * rhs.digits, self.decimal_index + rhs.decimal_index)
+    }
+}
+
+/// Division rounds according to the default `Context`, except that the quotient keeps
+/// at least as many digits after the decimal point as either operand has.
+///
+/// # Panics
+///
+/// Panics if `rhs` is zero.
+impl Div for Decimal {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self {
+        let mut context = Context::default();
+        context.scale = context.scale.max(self.decimal_index).max(rhs.decimal_index);
+        self.div_with(&rhs, context)
+            .expect("attempt to divide by zero")
+    }
+}
+
+/// The remainder has the same sign as `self`, like Rust's `%` on integers.
+///
+/// # Panics
+///
+/// Panics if `rhs` is zero.
+impl Rem for Decimal {
+    type Output = Self;
+    fn rem(self, rhs: Self) -> Self {
+        self.rem_with(&rhs, RoundingMode::Down)
+            .expect("attempt to calculate the remainder with a divisor of zero")
+    }
+}
+
+// the by-value operators above do the work; these just save callers from cloning
+macro_rules! forward_decimal_ops {
+    ($trait:ident, $func_name:ident, $assign_trait:ident, $assign_func_name:ident) => {
+        impl<'a> $trait<&'a Decimal> for Decimal {
+            type Output = Decimal;
+            fn $func_name(self, rhs: &'a Decimal) -> Decimal {
+                self.$func_name(rhs.clone())
+            }
+        }
+
+        impl<'a> $trait<Decimal> for &'a Decimal {
+            type Output = Decimal;
+            fn $func_name(self, rhs: Decimal) -> Decimal {
+                self.clone().$func_name(rhs)
+            }
+        }
+
+        impl<'a, 'b> $trait<&'b Decimal> for &'a Decimal {
+            type Output = Decimal;
+            fn $func_name(self, rhs: &'b Decimal) -> Decimal {
+                self.clone().$func_name(rhs.clone())
+            }
+        }
+
+        impl $assign_trait for Decimal {
+            fn $assign_func_name(&mut self, rhs: Decimal) {
+                let lhs = mem::replace(self, Decimal::from(0));
+                *self = lhs.$func_name(rhs);
+            }
+        }
+
+        impl<'a> $assign_trait<&'a Decimal> for Decimal {
+            fn $assign_func_name(&mut self, rhs: &'a Decimal) {
+                self.$assign_func_name(rhs.clone());
+            }
+        }
+    };
+}
+
+forward_decimal_ops!(Add, add, AddAssign, add_assign);
+forward_decimal_ops!(Sub, sub, SubAssign, sub_assign);
+forward_decimal_ops!(Mul, mul, MulAssign, mul_assign);
+forward_decimal_ops!(Div, div, DivAssign, div_assign);
+forward_decimal_ops!(Rem, rem, RemAssign, rem_assign);
+
+impl Neg for Decimal {
+    type Output = Decimal;
+    fn neg(self) -> Decimal {
+        Decimal::new(-self.digits, self.decimal_index)
+    }
+}
+
+impl Neg for &Decimal {
+    type Output = Decimal;
+    fn neg(self) -> Decimal {
+        -self.clone()
+    }
+}
+
+impl Sum for Decimal {
+    fn sum<I: Iterator<Item = Decimal>>(iter: I) -> Decimal {
+        iter.fold(Decimal::from(0), Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Decimal> for Decimal {
+    fn sum<I: Iterator<Item = &'a Decimal>>(iter: I) -> Decimal {
+        iter.fold(Decimal::from(0), Add::add)
+    }
+}
+
+impl Product for Decimal {
+    fn product<I: Iterator<Item = Decimal>>(iter: I) -> Decimal {
+        iter.fold(Decimal::from(1), Mul::mul)
+    }
+}
+
+impl<'a> Product<&'a Decimal> for Decimal {
+    fn product<I: Iterator<Item = &'a Decimal>>(iter: I) -> Decimal {
+        iter.fold(Decimal::from(1), Mul::mul)
+    }
+}
+
+/// The largest exponent, positive or negative, which parsing accepts
+///
+/// This limits how many digits an exponent can add to a parsed decimal, so that a short
+/// input can't demand an enormous value. Digits written out in full are not limited.
+pub const MAX_EXPONENT: usize = 10_000;
+
+/// An error which can be returned when parsing a `Decimal`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseDecimalError {
+    /// There were no digits before the exponent, if any
+    NoDigits,
+    /// A character was found which can't appear in a decimal
+    InvalidCharacter(char),
+    /// There was more than one decimal point
+    MultiplePoints,
+    /// The exponent was empty, or contained something other than digits
+    InvalidExponent,
+    /// The exponent was larger than `MAX_EXPONENT`
+    ExponentOutOfRange,
+}
+
+impl fmt::Display for ParseDecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseDecimalError::NoDigits => write!(f, "no digits in decimal"),
+            ParseDecimalError::InvalidCharacter(ch) => {
+                write!(f, "invalid character {:?} in decimal", ch)
+            }
+            ParseDecimalError::MultiplePoints => write!(f, "more than one decimal point"),
+            ParseDecimalError::InvalidExponent => write!(f, "invalid exponent in decimal"),
+            ParseDecimalError::ExponentOutOfRange => write!(f, "exponent out of range"),
+        }
+    }
+}
+
+impl Error for ParseDecimalError {}
+
+/// Parse a decimal such as `-12.34`, optionally followed by an exponent such as `e-7`
+/// or `E+12`. Surrounding whitespace is ignored.
+impl FromStr for Decimal {
+    type Err = ParseDecimalError;
+
+    fn from_str(input: &str) -> Result<Decimal, ParseDecimalError> {
+        // clear extraneous whitespace
+        let input = input.trim();
+        let (mantissa, exponent) = match input.find(&['e', 'E'][..]) {
+            Some(idx) => (&input[..idx], Some(&input[idx + 1..])),
+            None => (input, None),
+        };
+
+        // don't bother to trim extraneous zeroes
+        // leave it to users to manage their own memory
+
+        // now build a representation of the number to parse
+        let mut digits = String::with_capacity(mantissa.len());
+        let unsigned = if mantissa.starts_with('-') || mantissa.starts_with('+') {
+            digits.push_str(&mantissa[..1]);
+            &mantissa[1..]
+        } else {
+            mantissa
+        };
+        let mut decimal_index = None;
+        for ch in unsigned.chars() {
+            match ch {
+                '0'..='9' => {
+                    digits.push(ch);
+                    if let Some(idx) = decimal_index.as_mut() {
+                        *idx += 1;
+                    }
+                }
+                '.' => {
+                    if decimal_index.is_some() {
+                        return Err(ParseDecimalError::MultiplePoints);
+                    }
+                    decimal_index = Some(0)
+                }
+                _ => return Err(ParseDecimalError::InvalidCharacter(ch)),
+            }
+        }
+        // most values fit in an `i128`, which is much quicker to parse
+        let digits: Digits = match digits.parse::<i128>() {
+            Ok(digits) => digits.into(),
+            Err(_) => digits
+                .parse::<BigInt>()
+                .map_err(|_| ParseDecimalError::NoDigits)?
+                .into(),
+        };
+        let decimal_index = decimal_index.unwrap_or_default();
+
+        let exponent = match exponent {
+            None => 0,
+            Some(exponent) => parse_exponent(exponent)?,
+        };
+        // the exponent moves the decimal point; if it moves it right past the last
+        // digit, the digits need some trailing zeroes instead
+        let scale = (decimal_index as i64)
+            .checked_sub(exponent)
+            .ok_or(ParseDecimalError::ExponentOutOfRange)?;
+        if scale >= 0 {
+            Ok(Decimal::new(digits, scale as usize))
+        } else {
+            Ok(Decimal::new(digits.scale_up(scale.unsigned_abs() as usize), 0))
+        }
+    }
+}
+
+fn parse_exponent(exponent: &str) -> Result<i64, ParseDecimalError> {
+    let unsigned = exponent.trim_start_matches(&['-', '+'][..]);
+    if unsigned.is_empty()
+        || exponent.len() - unsigned.len() > 1
+        || !unsigned.chars().all(|ch| ch.is_ascii_digit())
+    {
+        return Err(ParseDecimalError::InvalidExponent);
+    }
+    // the exponent is well-formed, so this can only fail if it's too large
+    let exponent: i64 = exponent
+        .parse()
+        .map_err(|_| ParseDecimalError::ExponentOutOfRange)?;
+    if exponent.unsigned_abs() > MAX_EXPONENT as u64 {
+        return Err(ParseDecimalError::ExponentOutOfRange);
+    }
+    Ok(exponent)
+}
+
+/// An error which can be returned when converting between a `Decimal` and another
+/// numeric type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The value is outside the range of the target type
+    Overflow,
+    /// The value can't be represented exactly by the target type
+    Inexact,
+    /// The value is infinite or NaN, which no `Decimal` can represent
+    NonFinite,
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConversionError::Overflow => write!(f, "value out of range of the target type"),
+            ConversionError::Inexact => write!(f, "value not exactly representable"),
+            ConversionError::NonFinite => write!(f, "non-finite value has no decimal value"),
+        }
+    }
+}
+
+impl Error for ConversionError {}
+
+impl From<BigInt> for Decimal {
+    fn from(value: BigInt) -> Decimal {
+        Decimal::new(value, 0)
+    }
+}
+
+/// Fails with `Inexact` if the value has a fractional part.
+impl<'a> TryFrom<&'a Decimal> for BigInt {
+    type Error = ConversionError;
+
+    fn try_from(value: &'a Decimal) -> Result<BigInt, ConversionError> {
+        // reduction ensures that integers never have a decimal index
+        if value.decimal_index == 0 {
+            Ok(value.digits.to_bigint())
+        } else {
+            Err(ConversionError::Inexact)
+        }
+    }
+}
+
+impl TryFrom<Decimal> for BigInt {
+    type Error = ConversionError;
+
+    fn try_from(value: Decimal) -> Result<BigInt, ConversionError> {
+        (&value).try_into()
+    }
+}
+
+macro_rules! impl_integer_conversions {
+    ($($int:ty),*) => {
+        $(
+            /// Fails with `Inexact` if the value has a fractional part, or `Overflow` if
+            /// it's out of range.
+            impl<'a> TryFrom<&'a Decimal> for $int {
+                type Error = ConversionError;
+
+                fn try_from(value: &'a Decimal) -> Result<$int, ConversionError> {
+                    if value.decimal_index != 0 {
+                        return Err(ConversionError::Inexact);
+                    }
+                    match value.digits {
+                        Digits::Small(digits) => {
+                            <$int>::try_from(digits).map_err(|_| ConversionError::Overflow)
+                        }
+                        // only a `u128` can hold some values which don't fit in an `i128`
+                        Digits::Big(ref digits) => digits
+                            .to_string()
+                            .parse()
+                            .map_err(|_| ConversionError::Overflow),
+                    }
+                }
+            }
+
+            impl TryFrom<Decimal> for $int {
+                type Error = ConversionError;
+
+                fn try_from(value: Decimal) -> Result<$int, ConversionError> {
+                    (&value).try_into()
+                }
+            }
+        )*
+    }
+}
+
+macro_rules! impl_small_integer_conversions {
+    ($($int:ty),*) => {
+        $(
+            impl From<$int> for Decimal {
+                fn from(value: $int) -> Decimal {
+                    Decimal::new(value as i128, 0)
+                }
+            }
+        )*
+        impl_integer_conversions!($($int),*);
+    }
+}
+
+impl_small_integer_conversions!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+impl_integer_conversions!(i128, u128);
+
+impl From<u128> for Decimal {
+    fn from(value: u128) -> Decimal {
+        match i128::try_from(value) {
+            Ok(value) => Decimal::new(value, 0),
+            Err(_) => Decimal::new(bigint_from_u128(value), 0),
+        }
+    }
+}
+
+impl From<i128> for Decimal {
+    fn from(value: i128) -> Decimal {
+        Decimal::new(value, 0)
+    }
+}
+
+/// Converts to the decimal with the fewest digits which rounds to the same `f64`, so
+/// `0.1_f64` becomes exactly `0.1`.
+///
+/// Fails with `NonFinite` for infinities and NaN.
+impl TryFrom<f64> for Decimal {
+    type Error = ConversionError;
+
+    fn try_from(value: f64) -> Result<Decimal, ConversionError> {
+        if !value.is_finite() {
+            return Err(ConversionError::NonFinite);
+        }
+        // formatting produces the shortest representation which round-trips
+        Ok(format!("{:e}", value)
+            .parse()
+            .expect("finite floats always format as valid decimals"))
+    }
+}
+
+/// Converts to the nearest `f64`.
+///
+/// Fails with `Overflow` if the value is too large, or `Inexact` if converting the
+/// result back to a `Decimal` wouldn't produce the same value.
+impl<'a> TryFrom<&'a Decimal> for f64 {
+    type Error = ConversionError;
+
+    fn try_from(value: &'a Decimal) -> Result<f64, ConversionError> {
+        let float: f64 = format!("{:e}", value)
+            .parse()
+            .expect("decimals always format as valid floats");
+        if float.is_infinite() {
+            return Err(ConversionError::Overflow);
+        }
+        if <Decimal as TryFrom<f64>>::try_from(float)? != *value {
+            return Err(ConversionError::Inexact);
+        }
+        Ok(float)
+    }
+}
+
+impl TryFrom<Decimal> for f64 {
+    type Error = ConversionError;
+
+    fn try_from(value: Decimal) -> Result<f64, ConversionError> {
+        (&value).try_into()
+    }
+}
 
 macro_rules! auto_impl_decimal_cow {
     ($trait:ident, $func_name:ident, $digits_operation:expr, $return_type:ty) => {
@@ -140,7 +676,16 @@ macro_rules! auto_impl_decimal_cow {
 }
 
 auto_impl_decimal_cow!(PartialEq, eq, |a, b| a == b, bool);
-auto_impl_decimal_cow!(Ord, cmp, |a: &BigInt, b: &BigInt| a.cmp(b), Ordering);
+auto_impl_decimal_cow!(Ord, cmp, |a: &Digits, b: &Digits| a.cmp(b), Ordering);
+
+// values are always reduced, and so stored the same way whatever their original scale,
+// so hashing them is consistent with equality
+impl Hash for Decimal {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.digits.hash(state);
+        self.decimal_index.hash(state);
+    }
+}
 
 impl PartialOrd for Decimal {
     fn partial_cmp(&self, other: &Decimal) -> Option<Ordering> {
@@ -148,19 +693,106 @@ impl PartialOrd for Decimal {
     }
 }
 
+/// The canonical form of a decimal, such as `-12.34`
+///
+/// There's no exponent and no extraneous zeroes, so equal values are always displayed
+/// identically.
 impl fmt::Display for Decimal {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.digits.is_negative() { "-" } else { "" };
         // get a representation of the pure digits,
         // left-padded with zeroes
-        let digits = format!("{:0>width$}", self.digits, width = self.decimal_index);
-        if self.decimal_index == digits.len() {
-            write!(f, "0.{}", digits)
+        let digits = self.digits.to_string();
+        let digits = digits.trim_start_matches('-');
+        if self.decimal_index >= digits.len() {
+            let padding = "0".repeat(self.decimal_index - digits.len());
+            write!(f, "{}0.{}{}", sign, padding, digits)
         } else if self.decimal_index == 0 {
-            write!(f, "{}", digits)
+            write!(f, "{}{}", sign, digits)
         } else {
             let (before_index, after_index) = digits.split_at(digits.len() - self.decimal_index);
-            write!(f, "{}.{}", before_index, after_index)
+            write!(f, "{}{}.{}", sign, before_index, after_index)
+        }
+    }
+}
+
+impl Decimal {
+    /// Write in scientific notation, or engineering notation if the alternate flag is set
+    ///
+    /// The precision, if any, is the number of digits after the decimal point, rounding
+    /// half to even.
+    fn fmt_exponent(&self, f: &mut fmt::Formatter, marker: char) -> fmt::Result {
+        let engineering = f.alternate();
+        let sign = if self.digits.is_negative() { "-" } else { "" };
+        // the value is `digits * 10^exponent`
+        let mut digits = self.digits.to_string().trim_start_matches('-').to_string();
+        let mut exponent = -(self.decimal_index as i64);
+        // trailing zeroes aren't significant
+        while digits.len() > 1 && digits.ends_with('0') {
+            digits.pop();
+            exponent += 1;
+        }
+
+        let (shown_exponent, integral_digits) = loop {
+            // the exponent of the leading digit
+            let leading = exponent + digits.len() as i64 - 1;
+            let shown_exponent = if engineering {
+                leading - leading.rem_euclid(3)
+            } else {
+                leading
+            };
+            let integral_digits = (leading - shown_exponent + 1) as usize;
+            match f.precision() {
+                Some(precision) if digits.len() > integral_digits + precision => {
+                    let excess = digits.len() - integral_digits - precision;
+                    let rounded = RoundingMode::HalfEven.divide(
+                        &digits.parse().expect("digits are always valid"),
+                        &pow(BigInt::from(10_usize), excess),
+                    );
+                    digits = rounded.to_string();
+                    exponent += excess as i64;
+                    // rounding may have carried into a new leading digit, changing
+                    // the exponent, so go around again
+                }
+                _ => break (shown_exponent, integral_digits),
+            }
+        };
+
+        let width = integral_digits + f.precision().unwrap_or(0);
+        if digits.len() < width {
+            digits.push_str(&"0".repeat(width - digits.len()));
         }
+        let (integral, fractional) = digits.split_at(integral_digits);
+        if fractional.is_empty() {
+            write!(f, "{}{}{}{}", sign, integral, marker, shown_exponent)
+        } else {
+            write!(
+                f,
+                "{}{}.{}{}{}",
+                sign, integral, fractional, marker, shown_exponent
+            )
+        }
+    }
+}
+
+/// Scientific notation, such as `1.5e-7`
+///
+/// With the alternate flag (`{:#e}`), engineering notation is used instead, in which the
+/// exponent is always a multiple of three, such as `150e-9`.
+///
+/// A precision (`{:.3e}`) specifies the number of digits after the decimal point.
+impl fmt::LowerExp for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_exponent(f, 'e')
+    }
+}
+
+/// Scientific notation, such as `1.5E-7`
+///
+/// See `LowerExp` for the available options.
+impl fmt::UpperExp for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_exponent(f, 'E')
     }
 }
 
@@ -170,7 +802,7 @@ mod tests {
 
     #[test]
     fn test_display_temp() {
-        for test_str in vec!["0", "1", "20", "0.3", "0.04", "50.05", "66.0006", "0.007"] {
+        for test_str in ["0", "1", "20", "0.3", "0.04", "50.05", "66.0006", "0.007"] {
             println!(
                 "Decimal representation of \"{}\": {}",
                 test_str,
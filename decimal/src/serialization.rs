@@ -0,0 +1,113 @@
+//! `serde` support, enabled by the `serde` feature
+//!
+//! Decimals are serialized as strings in their canonical form, such as `"-12.34"`, so
+//! that no precision is lost however the format represents numbers. To serialize one as
+//! a number instead, use the `number` module:
+//!
+//! ```
+//! # extern crate decimal;
+//! # #[macro_use]
+//! # extern crate serde_derive;
+//! # extern crate serde_json;
+//! use decimal::Decimal;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Invoice {
+//!     #[serde(with = "decimal::serialization::number")]
+//!     total: Decimal,
+//! }
+//!
+//! # fn main() {
+//! let invoice = Invoice { total: "12.5".parse().unwrap() };
+//! assert_eq!(serde_json::to_string(&invoice).unwrap(), r#"{"total":12.5}"#);
+//! # }
+//! ```
+//!
+//! By default, a decimal is deserialized only from a string, so that formats which don't
+//! describe their own types, such as bincode, can read back what they wrote. The
+//! `number` module deserializes from either a string or a number, and so needs a format
+//! which does describe its types, such as JSON.
+
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::ser::{self, Serializer};
+use serde::{Deserialize, Serialize};
+
+use Decimal;
+
+impl Serialize for Decimal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+        deserializer.deserialize_str(DecimalVisitor)
+    }
+}
+
+/// Accepts a string or any number, though only self-describing formats offer a number
+struct DecimalVisitor;
+
+impl<'de> Visitor<'de> for DecimalVisitor {
+    type Value = Decimal;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a decimal number or a string containing one")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Decimal, E> {
+        value.parse().map_err(E::custom)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Decimal, E> {
+        Ok(Decimal::from(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Decimal, E> {
+        Ok(Decimal::from(value))
+    }
+
+    fn visit_i128<E: de::Error>(self, value: i128) -> Result<Decimal, E> {
+        Ok(Decimal::from(value))
+    }
+
+    fn visit_u128<E: de::Error>(self, value: u128) -> Result<Decimal, E> {
+        Ok(Decimal::from(value))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Decimal, E> {
+        value.try_into().map_err(E::custom)
+    }
+}
+
+/// Serialize a decimal as a number rather than a string
+///
+/// Integers are serialized as integers, if they fit in an `i64` or `u64`. Other values
+/// are serialized as an `f64`, which fails if that would lose precision.
+///
+/// Deserialization accepts either a number or a string. Since which kind of number was
+/// written depends on the value, this only works with self-describing formats.
+pub mod number {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+        if let Ok(value) = i64::try_from(value) {
+            serializer.serialize_i64(value)
+        } else if let Ok(value) = u64::try_from(value) {
+            serializer.serialize_u64(value)
+        } else {
+            let float = f64::try_from(value).map_err(|error| {
+                ser::Error::custom(format_args!("{} can't be a number: {}", value, error))
+            })?;
+            serializer.serialize_f64(float)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+        deserializer.deserialize_any(DecimalVisitor)
+    }
+}
@@ -1,5 +1,14 @@
+// some tests deliberately check that comparisons are false, rather than checking the inverse
+#![allow(clippy::nonminimal_bool)]
+
 extern crate decimal;
-use decimal::Decimal;
+extern crate num_bigint;
+use decimal::{
+    ConversionError, Context, Decimal, ParseDecimalError, RoundingMode, MAX_EXPONENT,
+};
+use num_bigint::BigInt;
+use std::collections::HashSet;
+use std::convert::{TryFrom, TryInto};
 
 /// Create a Decimal from a string literal
 ///
@@ -9,7 +18,7 @@ fn decimal(input: &str) -> Decimal {
 }
 
 /// Some big and precise values we can use for testing. [0] + [1] == [2]
-const BIGS: [&'static str; 3] = [
+const BIGS: [&str; 3] = [
     "100000000000000000000000000000000000000000000.00000000000000000000000000000000000000001",
     "100000000000000000000000000000000000000000000.00000000000000000000000000000000000000002",
     "200000000000000000000000000000000000000000000.00000000000000000000000000000000000000003",
@@ -288,3 +297,447 @@ fn test_add_away_decimal() {
 fn test_sub_away_decimal() {
     assert_eq!(decimal("1.1") - decimal("0.1"), decimal("1.0"))
 }
+
+// test division and rounding
+#[test]
+fn test_div() {
+    assert_eq!(decimal("1") / decimal("4"), decimal("0.25"));
+    assert_eq!(decimal("-7.5") / decimal("2.5"), decimal("-3"));
+    assert_eq!(
+        decimal(BIGS[2]).div_with(&decimal("1"), Context::new(41, RoundingMode::Down)),
+        Some(decimal(BIGS[2]))
+    );
+}
+
+#[test]
+fn test_div_default_context() {
+    assert_eq!(
+        decimal("2") / decimal("3"),
+        decimal("0.66666666666666666667")
+    );
+}
+
+#[test]
+fn test_div_keeps_operand_scale() {
+    let tiny = decimal("0.000000000000000000001");
+    assert_eq!(tiny.clone() / decimal("1"), tiny);
+    // rounded half to even at the scale of the dividend
+    assert_eq!(
+        decimal("0.000000000000000000003") / decimal("2"),
+        decimal("0.000000000000000000002")
+    );
+}
+
+#[test]
+fn test_div_with_context() {
+    let context = Context::new(2, RoundingMode::Down);
+    assert_eq!(
+        decimal("2").div_with(&decimal("3"), context),
+        Some(decimal("0.66"))
+    );
+    assert_eq!(
+        decimal("-2").div_with(&decimal("3"), context),
+        Some(decimal("-0.66"))
+    );
+}
+
+#[test]
+fn test_div_by_zero() {
+    assert_eq!(decimal("1").div_with(&decimal("0.0"), Context::default()), None);
+    assert_eq!(decimal("1").div_with(&decimal("0e40"), Context::default()), None);
+}
+
+#[test]
+#[should_panic]
+fn test_div_by_zero_panics() {
+    let _ = decimal("1") / decimal("0");
+}
+
+#[test]
+fn test_rem() {
+    assert_eq!(decimal("7") % decimal("2"), decimal("1"));
+    assert_eq!(decimal("-7") % decimal("2"), decimal("-1"));
+    assert_eq!(decimal("7.5") % decimal("0.2"), decimal("0.1"));
+}
+
+#[test]
+fn test_rem_with_rounding() {
+    assert_eq!(
+        decimal("-7").rem_with(&decimal("2"), RoundingMode::Floor),
+        Some(decimal("1"))
+    );
+    assert_eq!(
+        decimal("7").rem_with(&decimal("4"), RoundingMode::HalfEven),
+        Some(decimal("-1"))
+    );
+    assert_eq!(decimal("7").rem_with(&decimal("0"), RoundingMode::Floor), None);
+}
+
+#[test]
+fn test_round_modes() {
+    let cases = [
+        (RoundingMode::HalfEven, ["2", "2", "-2", "-2", "3"]),
+        (RoundingMode::HalfUp, ["3", "2", "-3", "-2", "3"]),
+        (RoundingMode::Down, ["2", "2", "-2", "-2", "2"]),
+        (RoundingMode::Up, ["3", "3", "-3", "-3", "3"]),
+        (RoundingMode::Ceiling, ["3", "3", "-2", "-2", "3"]),
+        (RoundingMode::Floor, ["2", "2", "-3", "-3", "2"]),
+    ];
+    for &(mode, expected) in cases.iter() {
+        let inputs = ["2.5", "2.1", "-2.5", "-2.1", "2.7"];
+        for (input, expected) in inputs.iter().zip(expected.iter()) {
+            assert_eq!(
+                decimal(input).round(0, mode),
+                decimal(expected),
+                "rounding {} with {:?}",
+                input,
+                mode
+            );
+        }
+    }
+}
+
+#[test]
+fn test_round_half_even_to_odd_digit() {
+    assert_eq!(decimal("3.5").round(0, RoundingMode::HalfEven), decimal("4"));
+    assert_eq!(decimal("0.125").round(2, RoundingMode::HalfEven), decimal("0.12"));
+    assert_eq!(decimal("0.135").round(2, RoundingMode::HalfEven), decimal("0.14"));
+}
+
+#[test]
+fn test_round_to_greater_scale() {
+    assert_eq!(decimal("1.25").round(5, RoundingMode::Down), decimal("1.25"));
+}
+
+// test scientific notation
+#[test]
+fn test_parse_exponent() {
+    assert_eq!(decimal("1.5e-7"), decimal("0.00000015"));
+    assert_eq!(decimal("-3E+12"), decimal("-3000000000000"));
+    assert_eq!(decimal("12.5e1"), decimal("125"));
+    assert_eq!(decimal("12.5e0"), decimal("12.5"));
+}
+
+#[test]
+fn test_from_str() {
+    assert_eq!("0.1".parse::<Decimal>(), Ok(decimal("0.1")));
+    assert_eq!(" -2.5e3 ".parse::<Decimal>(), Ok(decimal("-2500")));
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!("".parse::<Decimal>(), Err(ParseDecimalError::NoDigits));
+    assert_eq!("-.e5".parse::<Decimal>(), Err(ParseDecimalError::NoDigits));
+    assert_eq!(
+        "1.2.3".parse::<Decimal>(),
+        Err(ParseDecimalError::MultiplePoints)
+    );
+    assert_eq!(
+        "1-2".parse::<Decimal>(),
+        Err(ParseDecimalError::InvalidCharacter('-'))
+    );
+    assert_eq!("1e".parse::<Decimal>(), Err(ParseDecimalError::InvalidExponent));
+    assert_eq!("1e+-2".parse::<Decimal>(), Err(ParseDecimalError::InvalidExponent));
+    assert_eq!("1e2.5".parse::<Decimal>(), Err(ParseDecimalError::InvalidExponent));
+    assert_eq!(
+        "1e99999999999999999999".parse::<Decimal>(),
+        Err(ParseDecimalError::ExponentOutOfRange)
+    );
+    assert!(Decimal::try_from("1e").is_none());
+}
+
+#[test]
+fn test_parse_exponent_limit() {
+    let limit = MAX_EXPONENT as i64;
+    assert!(format!("1e{}", limit).parse::<Decimal>().is_ok());
+    assert!(format!("1e{}", -limit).parse::<Decimal>().is_ok());
+    assert!(format!("0.1e{}", -limit).parse::<Decimal>().is_ok());
+    for input in &[format!("1e{}", limit + 1), format!("1e{}", -limit - 1)] {
+        assert_eq!(
+            input.parse::<Decimal>(),
+            Err(ParseDecimalError::ExponentOutOfRange)
+        );
+    }
+}
+
+#[test]
+fn test_parse_long_fraction() {
+    // digits written out in full aren't limited like an exponent
+    let input = format!("0.{}1", "0".repeat(MAX_EXPONENT));
+    let parsed = input.parse::<Decimal>().unwrap();
+    assert_eq!(parsed.to_string(), input);
+    assert_eq!(
+        parsed * Decimal::from(10),
+        format!("1e-{}", MAX_EXPONENT).parse::<Decimal>().unwrap()
+    );
+}
+
+#[test]
+fn test_display_large_scale() {
+    // larger than any formatter width
+    let tiny: Decimal = (0..7)
+        .map(|_| format!("1e-{}", MAX_EXPONENT).parse::<Decimal>().unwrap())
+        .product();
+    let displayed = tiny.to_string();
+    assert_eq!(displayed.len(), 7 * MAX_EXPONENT + 2);
+    assert!(displayed.starts_with("0.000"));
+    assert!(displayed.ends_with("001"));
+}
+
+#[test]
+fn test_format_scientific() {
+    assert_eq!(format!("{:e}", decimal("0.00000015")), "1.5e-7");
+    assert_eq!(format!("{:E}", decimal("-3000000000000")), "-3E12");
+    assert_eq!(format!("{:e}", decimal("1")), "1e0");
+    assert_eq!(format!("{:e}", decimal("0")), "0e0");
+    assert_eq!(format!("{:e}", decimal("123.45")), "1.2345e2");
+}
+
+#[test]
+fn test_format_engineering() {
+    assert_eq!(format!("{:#e}", decimal("0.00000015")), "150e-9");
+    assert_eq!(format!("{:#e}", decimal("12345")), "12.345e3");
+    assert_eq!(format!("{:#E}", decimal("-0.5")), "-500E-3");
+    assert_eq!(format!("{:#e}", decimal("1000")), "1e3");
+}
+
+#[test]
+fn test_format_exponent_precision() {
+    assert_eq!(format!("{:.2e}", decimal("123.45")), "1.23e2");
+    assert_eq!(format!("{:.3e}", decimal("1.5")), "1.500e0");
+    assert_eq!(format!("{:.1e}", decimal("9.96")), "1.0e1");
+    assert_eq!(format!("{:#.1e}", decimal("999.96")), "1.0e3");
+    assert_eq!(format!("{:.0e}", decimal("2.5")), "2e0");
+}
+
+#[test]
+fn test_scientific_round_trip() {
+    for input in ["0.00000015", "-3000000000000", BIGS[0], "123.45"].iter() {
+        let value = decimal(input);
+        assert_eq!(decimal(&format!("{:e}", value)), value);
+        assert_eq!(decimal(&format!("{:#E}", value)), value);
+    }
+}
+
+// test conversions
+#[test]
+fn test_from_integers() {
+    assert_eq!(Decimal::from(-12_i64), decimal("-12"));
+    assert_eq!(Decimal::from(7_u8), decimal("7"));
+    assert_eq!(
+        Decimal::from(u128::MAX),
+        decimal("340282366920938463463374607431768211455")
+    );
+    assert_eq!(
+        Decimal::from(i128::MIN),
+        decimal("-170141183460469231731687303715884105728")
+    );
+    assert_eq!(Decimal::from(BigInt::from(42)), decimal("42"));
+}
+
+#[test]
+fn test_to_integers() {
+    assert_eq!(i64::try_from(decimal("-12.000")), Ok(-12));
+    assert_eq!(
+        u128::try_from(&decimal("340282366920938463463374607431768211455")),
+        Ok(u128::MAX)
+    );
+    assert_eq!(BigInt::try_from(decimal("42")), Ok(BigInt::from(42)));
+}
+
+#[test]
+fn test_to_integers_overflow() {
+    assert_eq!(u8::try_from(decimal("256")), Err(ConversionError::Overflow));
+    assert_eq!(u64::try_from(decimal("-1")), Err(ConversionError::Overflow));
+    assert_eq!(
+        i64::try_from(decimal(BIGS[0]).round(0, RoundingMode::Down)),
+        Err(ConversionError::Overflow)
+    );
+}
+
+#[test]
+fn test_to_integers_inexact() {
+    assert_eq!(i64::try_from(decimal("1.5")), Err(ConversionError::Inexact));
+    assert_eq!(BigInt::try_from(decimal(BIGS[0])), Err(ConversionError::Inexact));
+}
+
+#[test]
+fn test_from_f64() {
+    let from = |value: f64| -> Result<Decimal, ConversionError> { value.try_into() };
+    assert_eq!(from(0.1), Ok(decimal("0.1")));
+    assert_eq!(from(-1.5e-7), Ok(decimal("-0.00000015")));
+    assert_eq!(from(1e21), Ok(decimal("1000000000000000000000")));
+    assert_eq!(from(0.0), Ok(decimal("0")));
+    assert_eq!(from(f64::NAN), Err(ConversionError::NonFinite));
+    assert_eq!(from(f64::INFINITY), Err(ConversionError::NonFinite));
+}
+
+#[test]
+fn test_to_f64() {
+    assert_eq!(f64::try_from(decimal("0.1")), Ok(0.1));
+    assert_eq!(f64::try_from(decimal("-3E+12")), Ok(-3e12));
+    assert_eq!(
+        f64::try_from(decimal("0.10000000000000000001")),
+        Err(ConversionError::Inexact)
+    );
+    assert_eq!(f64::try_from(decimal("1e400")), Err(ConversionError::Overflow));
+    assert_eq!(f64::try_from(decimal("1e-400")), Err(ConversionError::Inexact));
+}
+
+#[test]
+fn test_f64_round_trip() {
+    for &value in [0.1, 1.0 / 3.0, 123456.789, -2.5e-300, 1.7976931348623157e308].iter() {
+        let decimal: Decimal = value.try_into().unwrap();
+        assert_eq!(f64::try_from(decimal), Ok(value));
+    }
+}
+
+// test values on either side of the fixed-size representation's range
+#[test]
+fn test_add_overflowing_fixed_size() {
+    let max = Decimal::from(i128::MAX);
+    let sum = max.clone() + decimal("1");
+    assert_eq!(sum, decimal("170141183460469231731687303715884105728"));
+    assert_eq!(sum - decimal("1"), max);
+}
+
+#[test]
+fn test_sub_overflowing_fixed_size() {
+    let min = Decimal::from(i128::MIN);
+    assert_eq!(
+        min.clone() - decimal("1"),
+        decimal("-170141183460469231731687303715884105729")
+    );
+    assert!(min.clone() - decimal("1") < min);
+}
+
+#[test]
+fn test_mul_overflowing_fixed_size() {
+    let large = decimal("10000000000000000000.00000000000000000001");
+    assert_eq!(
+        large.clone() * large,
+        decimal("100000000000000000000000000000000000000.2000000000000000000000000000000000000001")
+    );
+}
+
+#[test]
+fn test_equalize_overflowing_fixed_size() {
+    let large = decimal("100000000000000000000000000000000000");
+    let precise = decimal("0.0000000001");
+    assert_eq!(
+        large.clone() + precise.clone(),
+        decimal("100000000000000000000000000000000000.0000000001")
+    );
+    assert!(large > precise);
+}
+
+#[test]
+fn test_reduce_large_values() {
+    assert_eq!(
+        (decimal(BIGS[2]) - decimal("0.00000000000000000000000000000000000000003")).to_string(),
+        "200000000000000000000000000000000000000000000"
+    );
+}
+
+// test the canonical form
+#[test]
+fn test_display_negative_fractions() {
+    assert_eq!(decimal("-0.5").to_string(), "-0.5");
+    assert_eq!(decimal("-0.05").to_string(), "-0.05");
+    assert_eq!(decimal("-1.50").to_string(), "-1.5");
+}
+
+#[test]
+fn test_display_ignores_scale() {
+    assert_eq!(decimal("1.10").to_string(), decimal("1.1").to_string());
+    assert_eq!(decimal("-0.000").to_string(), "0");
+}
+
+#[test]
+fn test_hash_ignores_scale() {
+    let mut set = HashSet::new();
+    set.insert(decimal("1.10"));
+    set.insert(decimal("1.1"));
+    set.insert(decimal("01.100"));
+    set.insert(Decimal::from(1) + decimal("0.1"));
+    assert_eq!(set.len(), 1);
+    assert!(set.contains(&decimal("1.1000")));
+    assert!(!set.contains(&decimal("1.01")));
+}
+
+#[test]
+fn test_hash_ignores_representation() {
+    let big = decimal(BIGS[2]) - decimal(BIGS[1]) + decimal(BIGS[1]);
+    let mut set = HashSet::new();
+    set.insert(big);
+    assert!(set.contains(&decimal(BIGS[2])));
+    // a value which is briefly too large for the fixed-size representation
+    set.insert(Decimal::from(i128::MAX) + decimal("1") - decimal("1"));
+    assert!(set.contains(&Decimal::from(i128::MAX)));
+    // zero scaled past the fixed-size representation
+    set.insert(decimal("0e40"));
+    assert!(set.contains(&Decimal::from(0)));
+    assert_eq!(decimal("0e40").signum(), Decimal::from(0));
+}
+
+// test reference-based and compound operators
+#[test]
+fn test_ref_ops() {
+    let a = decimal("1.5");
+    let b = decimal("0.25");
+    assert_eq!(&a + &b, decimal("1.75"));
+    assert_eq!(&a - b.clone(), decimal("1.25"));
+    assert_eq!(a.clone() * &b, decimal("0.375"));
+    assert_eq!(&a / &b, decimal("6"));
+    assert_eq!(&a % &b, decimal("0"));
+    // the operands are still usable
+    assert_eq!(a, decimal("1.5"));
+    assert_eq!(b, decimal("0.25"));
+}
+
+#[test]
+fn test_assign_ops() {
+    let mut value = decimal("10");
+    value += decimal("2.5");
+    assert_eq!(value, decimal("12.5"));
+    value -= &decimal("0.5");
+    assert_eq!(value, decimal("12"));
+    value *= decimal("1.5");
+    assert_eq!(value, decimal("18"));
+    value /= &decimal("4");
+    assert_eq!(value, decimal("4.5"));
+    value %= decimal("2");
+    assert_eq!(value, decimal("0.5"));
+}
+
+#[test]
+fn test_neg() {
+    assert_eq!(-decimal("1.5"), decimal("-1.5"));
+    assert_eq!(-&decimal("-0.01"), decimal("0.01"));
+    assert_eq!(-decimal("0"), decimal("0"));
+    assert_eq!(-decimal(BIGS[0]), decimal("0") - decimal(BIGS[0]));
+    assert_eq!(
+        -Decimal::from(i128::MIN),
+        decimal("170141183460469231731687303715884105728")
+    );
+    assert_eq!(-(-Decimal::from(i128::MIN)), Decimal::from(i128::MIN));
+}
+
+#[test]
+fn test_abs_and_signum() {
+    assert_eq!(decimal("-2.5").abs(), decimal("2.5"));
+    assert_eq!(decimal("2.5").abs(), decimal("2.5"));
+    assert_eq!(decimal("-2.5").signum(), decimal("-1"));
+    assert_eq!(decimal("0.0").signum(), decimal("0"));
+    assert_eq!(decimal(BIGS[0]).signum(), decimal("1"));
+}
+
+#[test]
+fn test_sum_and_product() {
+    let values = vec![decimal("0.1"), decimal("0.2"), decimal("0.3")];
+    assert_eq!(values.iter().sum::<Decimal>(), decimal("0.6"));
+    assert_eq!(values.iter().product::<Decimal>(), decimal("0.006"));
+    assert_eq!(values.into_iter().sum::<Decimal>(), decimal("0.6"));
+    assert_eq!(Vec::<Decimal>::new().into_iter().sum::<Decimal>(), decimal("0"));
+    assert_eq!(Vec::<Decimal>::new().into_iter().product::<Decimal>(), decimal("1"));
+}
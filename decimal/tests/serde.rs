@@ -0,0 +1,85 @@
+#![cfg(feature = "serde")]
+
+extern crate bincode;
+extern crate decimal;
+extern crate serde_json;
+use decimal::Decimal;
+
+/// Create a Decimal from a string literal
+///
+/// Use only when you _know_ that your value is valid.
+fn decimal(input: &str) -> Decimal {
+    Decimal::try_from(input).expect("That was supposed to be a valid value")
+}
+
+const BIG: &str =
+    "100000000000000000000000000000000000000000000.00000000000000000000000000000000000000001";
+
+/// Serialize with `decimal::serialization::number`
+fn to_number(value: &Decimal) -> Result<String, serde_json::Error> {
+    let mut json = Vec::new();
+    decimal::serialization::number::serialize(value, &mut serde_json::Serializer::new(&mut json))?;
+    Ok(String::from_utf8(json).unwrap())
+}
+
+/// Deserialize with `decimal::serialization::number`
+fn from_number(json: &str) -> Result<Decimal, serde_json::Error> {
+    decimal::serialization::number::deserialize(&mut serde_json::Deserializer::from_str(json))
+}
+
+#[test]
+fn test_serialize_as_string() {
+    assert_eq!(
+        serde_json::to_string(&decimal("-12.3400")).unwrap(),
+        "\"-12.34\""
+    );
+    assert_eq!(
+        serde_json::to_string(&decimal("-0.05")).unwrap(),
+        "\"-0.05\""
+    );
+}
+
+#[test]
+fn test_deserialize_from_string() {
+    assert_eq!(
+        serde_json::from_str::<Decimal>("\"1.5e-7\"").unwrap(),
+        decimal("0.00000015")
+    );
+    assert!(serde_json::from_str::<Decimal>("\"1.2.3\"").is_err());
+}
+
+#[test]
+fn test_deserialize_from_number() {
+    assert_eq!(from_number("42").unwrap(), decimal("42"));
+    assert_eq!(from_number("-42").unwrap(), decimal("-42"));
+    assert_eq!(from_number("0.1").unwrap(), decimal("0.1"));
+    assert_eq!(from_number("\"0.1\"").unwrap(), decimal("0.1"));
+    // by default, only strings are accepted
+    assert!(serde_json::from_str::<Decimal>("42").is_err());
+}
+
+#[test]
+fn test_serialize_as_number() {
+    assert_eq!(to_number(&decimal("-42")).unwrap(), "-42");
+    assert_eq!(
+        to_number(&decimal("18446744073709551615")).unwrap(),
+        "18446744073709551615"
+    );
+    assert_eq!(to_number(&decimal("0.1")).unwrap(), "0.1");
+    assert!(to_number(&decimal("0.10000000000000000001")).is_err());
+}
+
+#[test]
+fn test_string_round_trip() {
+    let value = decimal(BIG);
+    let json = serde_json::to_string(&value).unwrap();
+    assert_eq!(serde_json::from_str::<Decimal>(&json).unwrap(), value);
+}
+
+#[test]
+fn test_non_self_describing_round_trip() {
+    for value in &[decimal("-12.34"), decimal("0"), decimal(BIG)] {
+        let bytes = bincode::serialize(value).unwrap();
+        assert_eq!(&bincode::deserialize::<Decimal>(&bytes).unwrap(), value);
+    }
+}
@@ -0,0 +1,45 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use forth::Forth;
+
+/// A word which expands to 2^12 calls of a trivial word.
+fn word_heavy(c: &mut Criterion) {
+    let mut f = Forth::new();
+    f.eval(": a 1 drop ;").unwrap();
+    for _ in 0..12 {
+        f.eval(": a a a ;").unwrap();
+    }
+    c.bench_function("word heavy", |b| b.iter(|| f.eval("a").unwrap()));
+}
+
+/// A counted loop calling a custom word on each iteration.
+fn loop_heavy(c: &mut Criterion) {
+    let mut f = Forth::new();
+    f.eval(": sq dup * ;").unwrap();
+    f.eval(": sum-squares 0 swap 0 do i sq + loop ;").unwrap();
+    c.bench_function("loop heavy", |b| {
+        b.iter(|| f.eval("1000 sum-squares drop").unwrap())
+    });
+}
+
+/// Straight-line arithmetic with no custom words.
+fn straight_line(c: &mut Criterion) {
+    let input = "1 2 + 3 * 4 - dup drop ".repeat(100);
+    let mut f = Forth::new();
+    c.bench_function("straight line", |b| {
+        b.iter(|| {
+            f.eval(&input).unwrap();
+            f.eval(&"drop ".repeat(100)).unwrap();
+        })
+    });
+}
+
+/// Defining words, as opposed to executing them.
+fn definitions(c: &mut Criterion) {
+    let input = ": foo dup * 1 + ; : bar foo foo ; : baz 10 0 do bar loop ; ".repeat(20);
+    c.bench_function("definitions", |b| {
+        b.iter(|| Forth::new().eval(&input).unwrap())
+    });
+}
+
+criterion_group!(benches, word_heavy, loop_heavy, straight_line, definitions);
+criterion_main!(benches);
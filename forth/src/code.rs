@@ -0,0 +1,249 @@
+use crate::{
+    high::Trace,
+    low::{Machine, Primitive},
+    Result, Value,
+};
+use std::io::Write;
+
+/// A single instruction of threaded code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Instruction {
+    Primitive(Primitive),
+    /// Call the definition with the given index, continuing with the next instruction
+    /// once it returns.
+    Call(usize),
+    /// Return to the caller. Every definition ends with one.
+    Return,
+    /// Continue execution at the given address.
+    Jump(usize),
+    /// Pop a flag; if it is zero, continue execution at the given address.
+    JumpIfZero(usize),
+    /// Pop an initial index and a limit, and begin a counted loop.
+    Do,
+    /// Increment the innermost loop index by one (or by a popped value, if `true`);
+    /// unless that crosses the limit, continue execution at the given address.
+    Loop(usize, bool),
+    /// Push the index of the loop `n` levels out from the innermost one.
+    LoopIndex(usize),
+    /// Print some literal text.
+    Print(String),
+}
+
+/// A single version of a custom word.
+#[derive(Debug)]
+struct Definition {
+    name: String,
+    version: usize,
+    /// The address of its first instruction.
+    start: usize,
+}
+
+/// An entry on the return stack.
+#[derive(Debug)]
+struct Frame {
+    /// The index of the definition being executed.
+    definition: usize,
+    /// The address at which to continue once it returns.
+    return_to: usize,
+}
+
+/// Every version of every custom word, compiled into one flat sequence of instructions.
+///
+/// Definitions are laid out one after another in the order in which they were defined.
+/// A `Call` refers to one specific definition, so redefining a word never affects
+/// existing definitions which use it.
+#[derive(Debug, Default)]
+pub(crate) struct Code {
+    instructions: Vec<Instruction>,
+    definitions: Vec<Definition>,
+}
+
+impl Code {
+    /// Append a definition, returning its index.
+    ///
+    /// Jump targets within `body` are relative to its start.
+    pub(crate) fn define(&mut self, name: String, version: usize, body: Vec<Instruction>) -> usize {
+        let start = self.instructions.len();
+        self.instructions
+            .extend(body.into_iter().map(|instruction| match instruction {
+                Instruction::Jump(target) => Instruction::Jump(start + target),
+                Instruction::JumpIfZero(target) => Instruction::JumpIfZero(start + target),
+                Instruction::Loop(target, by_popped) => {
+                    Instruction::Loop(start + target, by_popped)
+                }
+                instruction => instruction,
+            }));
+        self.instructions.push(Instruction::Return);
+        self.definitions.push(Definition {
+            name,
+            version,
+            start,
+        });
+        self.definitions.len() - 1
+    }
+
+    /// Discard a definition if it is the most recent, reclaiming its instructions.
+    ///
+    /// Older definitions are left in place: they may still be called.
+    pub(crate) fn discard(&mut self, definition: usize) {
+        if definition + 1 == self.definitions.len() {
+            let start = self.definitions[definition].start;
+            self.instructions.truncate(start);
+            self.definitions.pop();
+        }
+    }
+
+    /// The instructions of a definition, including its final `Return`.
+    fn body(&self, definition: usize) -> &[Instruction] {
+        let start = self.definitions[definition].start;
+        let end = self
+            .definitions
+            .get(definition + 1)
+            .map_or(self.instructions.len(), |next| next.start);
+        &self.instructions[start..end]
+    }
+
+    /// Whether one definition calls another.
+    pub(crate) fn calls(&self, caller: usize, callee: usize) -> bool {
+        self.body(caller).contains(&Instruction::Call(callee))
+    }
+
+    /// Describe a definition, one instruction per line.
+    ///
+    /// Jump targets are given relative to the start of the definition.
+    pub(crate) fn describe(&self, definition: usize) -> String {
+        let Definition {
+            name,
+            version,
+            start,
+        } = &self.definitions[definition];
+        let mut description = format!(": {} (version {})\n", name, version);
+        let body = self.body(definition);
+        // don't show the final `Return`; `;` implies it
+        for (idx, instruction) in body[..body.len() - 1].iter().enumerate() {
+            let text = match instruction {
+                Instruction::Primitive(primitive) => primitive.to_string(),
+                Instruction::Call(callee) => {
+                    let callee = &self.definitions[*callee];
+                    format!("{}#{}", callee.name, callee.version)
+                }
+                Instruction::Return => "exit".to_string(),
+                Instruction::Jump(target) => format!("branch -> {}", target - start),
+                Instruction::JumpIfZero(target) => format!("0branch -> {}", target - start),
+                Instruction::Do => "do".to_string(),
+                Instruction::Loop(target, false) => format!("loop -> {}", target - start),
+                Instruction::Loop(target, true) => format!("+loop -> {}", target - start),
+                Instruction::LoopIndex(0) => "i".to_string(),
+                Instruction::LoopIndex(1) => "j".to_string(),
+                Instruction::LoopIndex(depth) => format!("loop-index {}", depth),
+                Instruction::Print(text) => format!(".\" {}\"", text),
+            };
+            description.push_str(&format!("{:>4}: {}\n", idx, text));
+        }
+        description.push_str(";\n");
+        description
+    }
+
+    /// Execute a definition.
+    ///
+    /// If this fails, the definitions being executed at the time are recorded in the trace.
+    pub(crate) fn run<W: Write>(
+        &self,
+        definition: usize,
+        machine: &mut Machine<W>,
+        trace: &mut Trace,
+    ) -> Result {
+        let mut frames = Vec::new();
+        let result = self.execute(definition, machine, trace, &mut frames);
+        if result.is_err() {
+            trace.calls = frames
+                .iter()
+                .map(|frame| {
+                    let definition = &self.definitions[frame.definition];
+                    (definition.name.clone(), definition.version)
+                })
+                .collect();
+        }
+        result
+    }
+
+    fn execute<W: Write>(
+        &self,
+        definition: usize,
+        machine: &mut Machine<W>,
+        trace: &mut Trace,
+        frames: &mut Vec<Frame>,
+    ) -> Result {
+        // (index, limit) for each loop currently executing
+        let mut loops: Vec<(Value, Value)> = Vec::new();
+        let mut pc = self.definitions[definition].start;
+        trace.check_depth(frames.len())?;
+        frames.push(Frame {
+            definition,
+            return_to: pc,
+        });
+
+        loop {
+            trace.step()?;
+            let instruction = &self.instructions[pc];
+            pc += 1;
+            match instruction {
+                Instruction::Primitive(primitive) => machine.evaluate(*primitive)?,
+                Instruction::Call(definition) => {
+                    trace.check_depth(frames.len())?;
+                    frames.push(Frame {
+                        definition: *definition,
+                        return_to: pc,
+                    });
+                    pc = self.definitions[*definition].start;
+                }
+                Instruction::Return => {
+                    let frame = frames.pop().expect("every return has a frame");
+                    if frames.is_empty() {
+                        return Ok(());
+                    }
+                    pc = frame.return_to;
+                }
+                Instruction::Jump(target) => pc = *target,
+                Instruction::JumpIfZero(target) => {
+                    if machine.pop()? == 0 {
+                        pc = *target;
+                    }
+                }
+                Instruction::Do => {
+                    let index = machine.pop()?;
+                    let limit = machine.pop()?;
+                    loops.push((index, limit));
+                }
+                Instruction::Loop(start, by_popped) => {
+                    let step = if *by_popped { machine.pop()? } else { 1 };
+                    let (index, limit) = loops
+                        .last_mut()
+                        .expect("loop instructions are only compiled within a do loop");
+                    // if the index can't be represented, it has certainly crossed the limit
+                    let finished = match index.checked_add(step) {
+                        Some(next) if step < 0 => {
+                            *index = next;
+                            next < *limit
+                        }
+                        Some(next) => {
+                            *index = next;
+                            next >= *limit
+                        }
+                        None => true,
+                    };
+                    if finished {
+                        loops.pop();
+                    } else {
+                        pc = *start;
+                    }
+                }
+                Instruction::LoopIndex(depth) => {
+                    let (index, _) = loops[loops.len() - 1 - depth];
+                    machine.stack.push(index);
+                }
+                Instruction::Print(text) => machine.print(text)?,
+            }
+        }
+    }
+}
@@ -1,17 +1,29 @@
-use crate::{low::Primitive, Error, Value};
-use genawaiter::{rc::gen, yield_};
-use std::collections::HashMap;
-
-pub(crate) type Definitions = HashMap<String, Vec<Vec<Token>>>;
+use crate::{
+    code::{Code, Instruction},
+    low::{Machine, Primitive},
+    Error, Limits, Result,
+};
+use std::{collections::HashMap, io::Write, str::FromStr};
 
 #[derive(Debug, PartialEq, Eq)]
-pub(crate) enum Token {
+enum Token {
     BeginDefinition,
     EndDefinition,
+    /// A word which acts on the name following it, such as `variable` or `see`.
+    Parsing(Parsing),
+    /// List the names of all custom words.
+    Words,
     Primitive(Primitive),
     /// `Reference`s are always valid, and point to a specific version of the definition,
     /// preventing errors arising from redefinition.
-    Reference(String, usize),
+    Reference(usize),
+    /// Control-flow words are only meaningful within a definition, where they are
+    /// compiled into jump instructions.
+    Control(Control),
+    /// Print some literal text.
+    ///
+    /// When parsed, the text is empty; the evaluator fills it in from the input which follows.
+    Print(String),
 }
 
 impl Token {
@@ -19,130 +31,468 @@ impl Token {
     ///
     /// We can't use `std::str::FromStr`, even though it's directly applicable,
     /// because this method requires some context: the `Definitions`.
-    fn from_str(word: &str, definitions: &Definitions) -> Result<Token, Error> {
+    fn from_str(word: &str, definitions: &Definitions) -> std::result::Result<Token, Error> {
         match word {
             ":" => Ok(Token::BeginDefinition),
             ";" => Ok(Token::EndDefinition),
+            ".\"" => Ok(Token::Print(String::new())),
+            "variable" => Ok(Token::Parsing(Parsing::Variable)),
+            "constant" => Ok(Token::Parsing(Parsing::Constant)),
+            "see" => Ok(Token::Parsing(Parsing::See)),
+            "forget" => Ok(Token::Parsing(Parsing::Forget)),
+            "words" => Ok(Token::Words),
             _ => definitions
-                .get(word)
-                .map(|versions| Token::Reference(word.to_string(), versions.len() - 1))
-                .or_else(|| word.parse::<Primitive>().map(Token::Primitive).ok())
-                .ok_or(Error::UnknownWord),
+                .latest(word)
+                .map(Token::Reference)
+                .or_else(|| word.parse::<Control>().map(Token::Control).ok())
+                .map(Ok)
+                .unwrap_or_else(|| {
+                    word.parse::<Primitive>()
+                        .map(Token::Primitive)
+                        .map_err(|err| match err {
+                            // a number too large for a cell is still a number
+                            Error::Overflow => Error::Overflow,
+                            _ => Error::UnknownWord,
+                        })
+                }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Control {
+    If,
+    Else,
+    Then,
+    Do,
+    Loop,
+    PlusLoop,
+    I,
+    J,
+    Begin,
+    Until,
+    Again,
+    While,
+    Repeat,
+}
+
+impl FromStr for Control {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match s {
+            "if" => Control::If,
+            "else" => Control::Else,
+            "then" => Control::Then,
+            "do" => Control::Do,
+            "loop" => Control::Loop,
+            "+loop" => Control::PlusLoop,
+            "i" => Control::I,
+            "j" => Control::J,
+            "begin" => Control::Begin,
+            "until" => Control::Until,
+            "again" => Control::Again,
+            "while" => Control::While,
+            "repeat" => Control::Repeat,
+            _ => return Err(Error::UnknownWord),
+        })
+    }
+}
+
+/// Control-flow constructs which have been opened but not yet closed within a definition.
+#[derive(Debug, PartialEq, Eq)]
+enum Open {
+    /// `if` or `else`: the index of the jump whose target is not yet known.
+    If(usize),
+    /// `do`: the index of the start of the loop body.
+    Do(usize),
+    /// `begin`: the index of the start of the loop body.
+    Begin(usize),
+    /// `while`: the index of the start of the loop body, and of the jump out of it.
+    While(usize, usize),
+}
+
+/// Compile a control-flow word into the definition under construction.
+fn compile(control: Control, body: &mut Vec<Instruction>, open: &mut Vec<Open>) -> Result {
+    // set the target of a forward jump to the current end of the body
+    fn resolve(body: &mut [Instruction], at: usize) {
+        let here = body.len();
+        match &mut body[at] {
+            Instruction::Jump(target) | Instruction::JumpIfZero(target) => *target = here,
+            _ => unreachable!("only jumps are ever recorded as unresolved"),
+        }
+    }
+
+    let loop_depth = open.iter().filter(|o| matches!(o, Open::Do(_))).count();
+
+    match (control, open.pop()) {
+        (Control::If, outer) => {
+            open.extend(outer);
+            open.push(Open::If(body.len()));
+            body.push(Instruction::JumpIfZero(0));
+        }
+        (Control::Else, Some(Open::If(at))) => {
+            open.push(Open::If(body.len()));
+            body.push(Instruction::Jump(0));
+            resolve(body, at);
+        }
+        (Control::Then, Some(Open::If(at))) => resolve(body, at),
+        (Control::Do, outer) => {
+            open.extend(outer);
+            body.push(Instruction::Do);
+            open.push(Open::Do(body.len()));
+        }
+        (Control::Loop, Some(Open::Do(start))) => body.push(Instruction::Loop(start, false)),
+        (Control::PlusLoop, Some(Open::Do(start))) => body.push(Instruction::Loop(start, true)),
+        (Control::I, outer) | (Control::J, outer) => {
+            open.extend(outer);
+            let depth = if control == Control::I { 0 } else { 1 };
+            if depth >= loop_depth {
+                return Err(Error::InvalidWord);
+            }
+            body.push(Instruction::LoopIndex(depth));
+        }
+        (Control::Begin, outer) => {
+            open.extend(outer);
+            open.push(Open::Begin(body.len()));
+        }
+        (Control::Until, Some(Open::Begin(start))) => body.push(Instruction::JumpIfZero(start)),
+        (Control::Again, Some(Open::Begin(start))) => body.push(Instruction::Jump(start)),
+        (Control::While, Some(Open::Begin(start))) => {
+            open.push(Open::While(start, body.len()));
+            body.push(Instruction::JumpIfZero(0));
         }
+        (Control::Repeat, Some(Open::While(start, at))) => {
+            body.push(Instruction::Jump(start));
+            resolve(body, at);
+        }
+        _ => return Err(Error::InvalidWord),
     }
+    Ok(())
 }
 
+/// Words which act on the name following them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Parsing {
+    Colon,
+    Variable,
+    Constant,
+    See,
+    Forget,
+}
 #[derive(Debug, PartialEq, Eq)]
 enum Mode {
     Normal,
-    ExpectName,
-    Definition(String, Vec<Token>),
+    ExpectName(Parsing),
+    Definition(String, Vec<Instruction>, Vec<Open>),
 }
 
-/// Evaluate an input string, emitting an iterator of either primitives or errors as they arise.
-pub(crate) fn evaluate<'a>(
-    input: &'a str,
-    definitions: &'a mut Definitions,
-) -> impl 'a + Iterator<Item = Result<Primitive, Error>> {
-    gen!({
-        let mut mode = Mode::Normal;
-
-        for word in input.split_whitespace() {
-            let word = word.to_lowercase();
-
-            // we want to parse the token in all cases _except_ when the mode is ExpectName.
-            // this is because token parsing involves a little validation: if the token doesn't refer
-            // to a word already in the definitions, it'll fail. ExpectName is when we
-            let token = match mode {
-                Mode::Normal | Mode::Definition(..) => {
-                    match Token::from_str(&word, &definitions) {
-                        Ok(token) => token,
-                        Err(err) => {
-                            yield_!(Err(err));
-                            continue;
+/// A change to the definitions.
+#[derive(Debug)]
+pub(crate) enum Change {
+    /// A new version of the word's definition was added.
+    Defined(String),
+    /// The most recent version of the word's definition, with the given index, was removed.
+    Forgot(String, usize),
+}
+
+/// Where evaluation has got to, so that errors can be located, and how it has
+/// changed the definitions, so that those changes can be undone.
+#[derive(Debug, Default)]
+pub(crate) struct Trace {
+    /// The byte offset of the input word currently being evaluated.
+    pub(crate) offset: usize,
+    /// The custom words executing when an error occurred, outermost first.
+    pub(crate) calls: Vec<(String, usize)>,
+    /// The changes made to the definitions, in order.
+    pub(crate) changes: Vec<Change>,
+    /// The limits on how much work evaluation may do.
+    limits: Limits,
+    /// The number of steps taken so far.
+    steps: u64,
+}
+
+impl Trace {
+    pub(crate) fn new(limits: Limits) -> Trace {
+        Trace {
+            limits,
+            ..Trace::default()
+        }
+    }
+
+    /// Count a single step: an input word, or an instruction of a definition.
+    pub(crate) fn step(&mut self) -> Result {
+        let steps = self.steps + 1;
+        if self.limits.steps.is_some_and(|limit| steps > limit) {
+            return Err(Error::StepLimitExceeded);
+        }
+        self.steps = steps;
+        Ok(())
+    }
+
+    /// Check whether another custom word may be called, given how many are already executing.
+    pub(crate) fn check_depth(&self, depth: usize) -> Result {
+        if self.limits.depth.is_some_and(|limit| depth >= limit) {
+            return Err(Error::RecursionLimit);
+        }
+        Ok(())
+    }
+}
+
+/// The custom words, and their compiled code.
+#[derive(Debug, Default)]
+pub(crate) struct Definitions {
+    /// Each version of each word's definition, oldest first, as an index into `code`.
+    words: HashMap<String, Vec<usize>>,
+    code: Code,
+}
+
+impl Definitions {
+    /// The index of the most recent version of a word's definition.
+    fn latest(&self, word: &str) -> Option<usize> {
+        self.words
+            .get(word)
+            .and_then(|versions| versions.last().copied())
+    }
+
+    /// Add a new version of a word's definition.
+    fn define(&mut self, trace: &mut Trace, word: String, body: Vec<Instruction>) {
+        let versions = self.words.entry(word.clone()).or_default();
+        versions.push(self.code.define(word.clone(), versions.len(), body));
+        trace.changes.push(Change::Defined(word));
+    }
+
+    /// Remove the most recent version of a word's definition.
+    ///
+    /// This fails if any definition calls that version: `Reference`s must always be valid.
+    fn forget(&mut self, trace: &mut Trace, word: String) -> Result {
+        let definition = self.latest(&word).ok_or(Error::UnknownWord)?;
+        let code = &self.code;
+        if self
+            .words
+            .values()
+            .flatten()
+            .any(|&caller| code.calls(caller, definition))
+        {
+            return Err(Error::InvalidWord);
+        }
+
+        let versions = self
+            .words
+            .get_mut(&word)
+            .expect("word is known to be defined");
+        versions.pop();
+        if versions.is_empty() {
+            self.words.remove(&word);
+        }
+        trace.changes.push(Change::Forgot(word, definition));
+        Ok(())
+    }
+
+    /// Undo the given changes to the definitions, most recent first.
+    pub(crate) fn undo(&mut self, changes: Vec<Change>) {
+        for change in changes.into_iter().rev() {
+            match change {
+                Change::Defined(word) => {
+                    if let Some(versions) = self.words.get_mut(&word) {
+                        if let Some(definition) = versions.pop() {
+                            self.code.discard(definition);
+                        }
+                        if versions.is_empty() {
+                            self.words.remove(&word);
                         }
                     }
                 }
-                Mode::ExpectName => {
-                    // we need some token here, but it's arbitrary and ignored.
-                    // we could define a custom `Invalid` token, but that would pollute
-                    // all relevant match statements; better to use a stand-in.
-                    Token::EndDefinition
+                Change::Forgot(word, definition) => {
+                    self.words.entry(word).or_default().push(definition);
                 }
-            };
-
-            match mode {
-                Mode::Normal => {
-                    // generator syntax in Rust is still kind of clunky, particularly here:
-                    // what I really want is a Python-ish `yield from eval_token_normal(...)`
-                    for item in eval_token_normal(&token, &mut mode, &definitions) {
-                        yield_!(item);
-                    }
+            }
+        }
+    }
+
+    /// List the names of all custom words, in alphabetical order.
+    fn words(&self) -> String {
+        let mut names: Vec<_> = self.words.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names.iter().map(|name| format!("{} ", name)).collect()
+    }
+
+    /// Describe the most recent version of a word's definition, one instruction per line.
+    fn see(&self, word: &str) -> std::result::Result<String, Error> {
+        let definition = self.latest(word).ok_or(Error::UnknownWord)?;
+        Ok(self.code.describe(definition))
+    }
+}
+
+/// Splits input into whitespace-separated words, while permitting parsing words
+/// such as `."` to consume the raw text which follows them.
+struct Words<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Words<'a> {
+    fn new(input: &'a str) -> Self {
+        Words { input, position: 0 }
+    }
+
+    /// Consume raw text up to the given delimiter, which is also consumed.
+    ///
+    /// The single whitespace character separating the text from the preceding word is
+    /// not part of the text. Returns `None` if the delimiter is never found.
+    fn until(&mut self, delimiter: char) -> Option<&'a str> {
+        let rest = &self.input[self.position..];
+        let start = self.position + rest.chars().next().map_or(0, char::len_utf8);
+        let len = self.input[start..].find(delimiter)?;
+        self.position = start + len + delimiter.len_utf8();
+        Some(&self.input[start..start + len])
+    }
+}
+
+impl<'a> Iterator for Words<'a> {
+    /// A word, and its byte offset within the input.
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = &self.input[self.position..];
+        let start = self.position + rest.find(|c: char| !c.is_whitespace())?;
+        let rest = &self.input[start..];
+        let len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        self.position = start + len;
+        Some((start, &rest[..len]))
+    }
+}
+/// Evaluate an input string.
+///
+/// `trace` is kept up to date with the location of the input word being evaluated.
+pub(crate) fn evaluate<W: Write>(
+    input: &str,
+    definitions: &mut Definitions,
+    machine: &mut Machine<W>,
+    trace: &mut Trace,
+) -> Result {
+    let mut mode = Mode::Normal;
+
+    let mut words = Words::new(input);
+    while let Some((offset, word)) = words.next() {
+        trace.offset = offset;
+        let word = word.to_lowercase();
+
+        // we want to parse the token in all cases _except_ when the mode is ExpectName.
+        // this is because token parsing involves a little validation: if the token doesn't
+        // refer to a word already in the definitions, it'll fail.
+        if let Mode::ExpectName(parsing) = mode {
+            // numbers aren't allowed as custom words, even those too large for a cell
+            if matches!(
+                Primitive::from_str(&word),
+                Ok(Primitive::Num(_)) | Err(Error::Overflow)
+            ) {
+                return Err(Error::InvalidWord);
+            }
+            mode = eval_name(parsing, word, definitions, machine, trace)?;
+            continue;
+        }
+
+        let token = match Token::from_str(&word, definitions)? {
+            Token::Print(_) => {
+                Token::Print(words.until('"').ok_or(Error::InvalidWord)?.to_string())
+            }
+            token => token,
+        };
+
+        match mode {
+            Mode::Normal => {
+                trace.step()?;
+                mode = eval_token_normal(token, definitions, machine, trace)?;
+            }
+            // invariant in definition handler: the only instructions which ever get
+            // pushed to `body` are primitives, calls to valid definitions, text to print,
+            // and those produced by `compile`, whose jump targets are within the definition.
+            Mode::Definition(_, ref mut body, ref mut open) => match token {
+                Token::BeginDefinition | Token::Parsing(..) | Token::Words => {
+                    return Err(Error::InvalidWord);
                 }
-                Mode::ExpectName => {
-                    // numbers aren't allowed as custom words (and we skipped the normal parsing)
-                    if word.parse::<Value>().is_ok() {
-                        yield_!(Err(Error::InvalidWord));
-                        continue;
+                Token::Primitive(primitive) => body.push(Instruction::Primitive(primitive)),
+                Token::Reference(definition) => body.push(Instruction::Call(definition)),
+                Token::Print(text) => body.push(Instruction::Print(text)),
+                Token::Control(control) => compile(control, body, open)?,
+                Token::EndDefinition if !open.is_empty() => return Err(Error::InvalidWord),
+                Token::EndDefinition => {
+                    if let Mode::Definition(word, body, _) =
+                        std::mem::replace(&mut mode, Mode::Normal)
+                    {
+                        definitions.define(trace, word, body);
+                    } else {
+                        unreachable!("this is definitely the current mode; we just don't have a better way to extract its values");
                     }
-                    mode = Mode::Definition(word, Vec::new());
                 }
-                // invariant in definition handler: the only tokens which ever get
-                // pushed to `subsequent` are primitives and (valid) references.
-                Mode::Definition(_, ref mut subsequent) => match token {
-                    Token::BeginDefinition => yield_!(Err(Error::InvalidWord)),
-                    Token::Primitive(..) | Token::Reference(..) => subsequent.push(token),
-                    Token::EndDefinition => {
-                        if let Mode::Definition(word, definition) =
-                            std::mem::replace(&mut mode, Mode::Normal)
-                        {
-                            definitions.entry(word).or_default().push(definition);
-                        } else {
-                            unreachable!("this is definitely the current mode; we just don't have a better way to extract its values");
-                        }
-                    }
-                },
-            }
+            },
+            Mode::ExpectName(_) => unreachable!("names are handled before parsing a token"),
         }
+    }
 
-        if mode != Mode::Normal {
-            yield_!(Err(Error::InvalidWord));
+    if mode != Mode::Normal {
+        trace.offset = input.len();
+        return Err(Error::InvalidWord);
+    }
+    Ok(())
+}
+
+/// Evaluate the name following a parsing word, returning the mode to continue in.
+fn eval_name<W: Write>(
+    parsing: Parsing,
+    word: String,
+    definitions: &mut Definitions,
+    machine: &mut Machine<W>,
+    trace: &mut Trace,
+) -> std::result::Result<Mode, Error> {
+    // variables and constants are just definitions which push a single number
+    let value = match parsing {
+        Parsing::Colon => return Ok(Mode::Definition(word, Vec::new(), Vec::new())),
+        Parsing::Variable => {
+            machine.evaluate(Primitive::Here)?;
+            let address = machine.pop()?;
+            machine.evaluate(Primitive::Num(1))?;
+            machine.evaluate(Primitive::Allot)?;
+            address
+        }
+        Parsing::Constant => machine.pop()?,
+        Parsing::See => {
+            let description = definitions.see(&word)?;
+            machine.print(&description)?;
+            return Ok(Mode::Normal);
         }
-    }).into_iter()
+        Parsing::Forget => {
+            definitions.forget(trace, word)?;
+            return Ok(Mode::Normal);
+        }
+    };
+    definitions.define(
+        trace,
+        word,
+        vec![Instruction::Primitive(Primitive::Num(value))],
+    );
+    Ok(Mode::Normal)
 }
 
-/// Evaluate a token in normal mode.
-///
-/// This function is broken out so it can recursively call itself when
-/// expanding custom words.
-///
-/// Its recursion would get weird if it weren't for the invariant in the definition handler;
-/// all definitions are known to contain only primitives or valid references.
+/// Evaluate a token in normal mode, returning the mode to continue in.
 ///
-/// Note that it returns a `Box<dyn Iterator>` instead of simply `impl Iterator`. This works
-/// around a rustc limitation: Rust has trouble with recursive opaque types unless you box them
-/// up in this way.
-fn eval_token_normal<'a>(
-    token: &'a Token,
-    mode: &'a mut Mode,
-    definitions: &'a Definitions,
-) -> Box<dyn 'a + Iterator<Item = Result<Primitive, Error>>> {
-    Box::new(
-        gen!({
-            match token {
-                Token::Primitive(primitive) => yield_!(Ok(*primitive)),
-                Token::BeginDefinition => *mode = Mode::ExpectName,
-                Token::EndDefinition => yield_!(Err(Error::InvalidWord)),
-                Token::Reference(word, index) => {
-                    let definition = &definitions[word][*index];
-                    for token in definition {
-                        for item in eval_token_normal(token, mode, definitions) {
-                            yield_!(item);
-                        }
-                    }
-                }
-            }
-        })
-        .into_iter(),
-    )
+/// Custom words are executed by the threaded code in `Definitions`; all definitions are
+/// known to contain only primitives, calls to valid definitions, and valid jumps.
+fn eval_token_normal<W: Write>(
+    token: Token,
+    definitions: &Definitions,
+    machine: &mut Machine<W>,
+    trace: &mut Trace,
+) -> std::result::Result<Mode, Error> {
+    match token {
+        Token::Primitive(primitive) => machine.evaluate(primitive)?,
+        Token::Reference(definition) => definitions.code.run(definition, machine, trace)?,
+        Token::Print(text) => machine.print(&text)?,
+        Token::Words => machine.print(&definitions.words())?,
+        Token::BeginDefinition => return Ok(Mode::ExpectName(Parsing::Colon)),
+        Token::Parsing(parsing) => return Ok(Mode::ExpectName(parsing)),
+        Token::EndDefinition | Token::Control(..) => return Err(Error::InvalidWord),
+    }
+    Ok(Mode::Normal)
 }
@@ -1,62 +1,257 @@
-//! This implementation operates in two separate layers: a low-level layer and a
-//! high-level layer.
+//! This implementation operates in three separate layers: a low-level layer, a
+//! code layer, and a high-level layer.
 //!
-//! The low-level layer handles the fundamental stack operations: given a stream
-//! of primitives, it manipulates the stack appropriately.
+//! The low-level layer handles the fundamental stack operations: given a
+//! primitive, it manipulates the stack, memory, and output appropriately.
 //!
-//! The high-level layer handles custom definitions. Given a stream of words
-//! which may or may not include definitions, it generates a stream of
-//! primitives to feed into the low level layer.
+//! The code layer holds every custom definition, compiled into one flat
+//! sequence of instructions: primitives, calls to other definitions, returns,
+//! and jumps. It executes them with a program counter and an explicit return
+//! stack, so deeply nested custom words don't consume the native stack.
 //!
-//! Architecturally, we're implementing the high-level layer as a generator.
-//! This gives us lots of power to recurse and insert an arbitrary number of
-//! yielded items, while keeping the interface requirements for the low-level
-//! layer minimal.
+//! The high-level layer parses the input. Outside of definitions, it feeds each
+//! word directly to one of the layers below; within definitions, it compiles
+//! words into instructions for the code layer.
+//!
+//! Control flow (`if`, `do`, `begin`, ...) is only permitted within definitions,
+//! where it is compiled into jumps within the definition body.
 //!
 //! This implementation was designed to satisfy two properties:
 //!
 //!  - Minimal allocation. Nothing is cloned which isn't required by the
 //!    structure of the problem and the language.
-//!  - Use an easy-to-understand recursive high-level evaluator instead of
-//!    eagerly copying the expansion of custom words, avoiding the attack laid
+//!  - Calls refer to a specific version of each definition instead of eagerly
+//!    copying the expansion of custom words, avoiding the attack laid
 //!    out in https://github.com/exercism/rust/issues/1221.
 
-use high::Definitions;
+use high::{Definitions, Trace};
+use low::Machine;
+use std::{fmt, io::Write};
 
+mod code;
 mod high;
 mod low;
 
+/// A single stack cell. Cells are 32 bits wide by default, or 64 bits with the `i64` feature.
+#[cfg(not(feature = "i64"))]
 pub type Value = i32;
+/// A single stack cell. Cells are 32 bits wide by default, or 64 bits with the `i64` feature.
+#[cfg(feature = "i64")]
+pub type Value = i64;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Error {
     DivisionByZero,
+    Overflow,
     StackUnderflow,
     UnknownWord,
     InvalidWord,
+    /// A memory access was outside the allotted memory.
+    InvalidAddress,
+    /// Writing to the output failed.
+    Io(std::io::ErrorKind),
+    /// Evaluation took more steps than its limit permits.
+    StepLimitExceeded,
+    /// Custom words were nested more deeply than the limit permits.
+    RecursionLimit,
+    /// Allotting would make memory larger than its limit permits.
+    MemoryLimitExceeded,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::DivisionByZero => write!(f, "division by zero"),
+            Error::Overflow => write!(f, "arithmetic overflow"),
+            Error::StackUnderflow => write!(f, "stack underflow"),
+            Error::UnknownWord => write!(f, "unknown word"),
+            Error::InvalidWord => write!(f, "invalid word"),
+            Error::InvalidAddress => write!(f, "invalid memory address"),
+            Error::Io(kind) => write!(f, "output failed: {:?}", kind),
+            Error::StepLimitExceeded => write!(f, "step limit exceeded"),
+            Error::RecursionLimit => write!(f, "recursion limit exceeded"),
+            Error::MemoryLimitExceeded => write!(f, "memory limit exceeded"),
+        }
+    }
 }
 
+impl std::error::Error for Error {}
+
 pub type Result = std::result::Result<(), Error>;
 
-#[derive(Debug, Default)]
-pub struct Forth {
-    stack: Vec<Value>,
+/// Limits on the work a single evaluation may do, and on memory, guarding against hostile input.
+///
+/// By default, the work evaluation may do is unlimited, but memory is limited to
+/// 2^20 cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// The maximum number of steps taken: each input word, and each instruction
+    /// of each custom word's definition as it is executed, counts as one step.
+    pub steps: Option<u64>,
+    /// The maximum number of custom words which may be executing within one another.
+    pub depth: Option<usize>,
+    /// The maximum number of cells of memory which may be allotted in total.
+    pub memory: Option<usize>,
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        Limits {
+            steps: None,
+            depth: None,
+            memory: Some(1 << 20),
+        }
+    }
+}
+
+/// An error, along with where in the input and the definitions it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub error: Error,
+    /// The input word being evaluated when the error occurred.
+    ///
+    /// Empty if the error occurred at the end of the input, i.e. an unterminated definition.
+    pub word: String,
+    /// The byte offset of `word` within the input.
+    pub offset: usize,
+    /// The custom words executing when the error occurred, outermost first.
+    ///
+    /// Each is paired with the version of its definition which was executing:
+    /// 0 for the first definition, incrementing with each redefinition.
+    pub call_chain: Vec<(String, usize)>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.word.is_empty() {
+            write!(f, "{} at end of input", self.error)?;
+        } else {
+            write!(
+                f,
+                "{} at `{}` (byte {})",
+                self.error, self.word, self.offset
+            )?;
+        }
+        for (idx, (word, version)) in self.call_chain.iter().enumerate() {
+            let sep = if idx == 0 { ", in" } else { " ->" };
+            write!(f, "{} {} (version {})", sep, word, version)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Diagnostic {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<Diagnostic> for Error {
+    fn from(diagnostic: Diagnostic) -> Error {
+        diagnostic.error
+    }
+}
+
+/// A Forth interpreter.
+///
+/// Output words (`.`, `emit`, ...) write to `W`: by default, an in-memory buffer.
+#[derive(Debug)]
+pub struct Forth<W = Vec<u8>> {
+    machine: Machine<W>,
     definitions: Definitions,
+    /// Whether to roll back after a failed evaluation.
+    atomic: bool,
+    limits: Limits,
 }
 
 impl Forth {
     pub fn new() -> Forth {
         Forth::default()
     }
+}
+
+impl<W: Default + Write> Default for Forth<W> {
+    fn default() -> Self {
+        Forth::with_output(W::default())
+    }
+}
+
+impl<W: Write> Forth<W> {
+    /// Construct an interpreter whose output words write to the given sink.
+    pub fn with_output(output: W) -> Forth<W> {
+        let limits = Limits::default();
+        let mut machine = Machine::new(output);
+        machine.memory_limit = limits.memory;
+        Forth {
+            machine,
+            definitions: Definitions::default(),
+            atomic: false,
+            limits,
+        }
+    }
+
+    /// Set whether evaluation is atomic.
+    ///
+    /// When it is, an evaluation which fails restores the stack, memory, and definitions
+    /// to their state before it began. Output which has already been written can't be
+    /// taken back.
+    ///
+    /// Atomic evaluation copies the stack and memory before each evaluation.
+    pub fn set_atomic(&mut self, atomic: bool) {
+        self.atomic = atomic;
+    }
+
+    /// Set the limits on the work each evaluation may do, and on memory.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+        self.machine.memory_limit = limits.memory;
+    }
 
     pub fn stack(&self) -> &[Value] {
-        &self.stack
+        &self.machine.stack
+    }
+
+    /// The output sink: for `Forth::new()`, a buffer of everything printed so far.
+    pub fn output(&self) -> &W {
+        &self.machine.output
     }
 
     pub fn eval(&mut self, input: &str) -> Result {
-        for primitive in high::evaluate(input, &mut self.definitions) {
-            low::evaluate(&mut self.stack, primitive?)?;
+        self.eval_diagnostic(input).map_err(Error::from)
+    }
+
+    /// Evaluate some input, reporting where any error occurred.
+    pub fn eval_diagnostic(&mut self, input: &str) -> std::result::Result<(), Diagnostic> {
+        let mut trace = Trace::new(self.limits);
+        let snapshot = if self.atomic {
+            Some((self.machine.stack.clone(), self.machine.memory.clone()))
+        } else {
+            None
+        };
+        let result = self.eval_traced(input, &mut trace);
+        if let (Err(_), Some((stack, memory))) = (&result, snapshot) {
+            self.machine.stack = stack;
+            self.machine.memory = memory;
+            self.definitions.undo(std::mem::take(&mut trace.changes));
         }
-        Ok(())
+        result.map_err(|error| Diagnostic {
+            error,
+            word: input[trace.offset..]
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            offset: trace.offset,
+            call_chain: trace.calls,
+        })
+    }
+
+    fn eval_traced(&mut self, input: &str, trace: &mut Trace) -> Result {
+        high::evaluate(input, &mut self.definitions, &mut self.machine, trace)?;
+        trace.offset = input.len();
+        self.machine
+            .output
+            .flush()
+            .map_err(|err| Error::Io(err.kind()))
     }
 }
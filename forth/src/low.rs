@@ -1,5 +1,5 @@
 use crate::{Error, Result, Value};
-use std::str::FromStr;
+use std::{convert::TryFrom, fmt, io::Write, num::IntErrorKind, str::FromStr};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum Primitive {
@@ -11,6 +11,33 @@ pub(crate) enum Primitive {
     Drop,
     Swap,
     Over,
+    Eq,
+    Lt,
+    Gt,
+    ZeroEq,
+    And,
+    Or,
+    Invert,
+    Mod,
+    DivMod,
+    Rot,
+    MinusRot,
+    Nip,
+    Tuck,
+    Pick,
+    Roll,
+    TwoDup,
+    TwoDrop,
+    TwoSwap,
+    Dot,
+    Emit,
+    Cr,
+    DotS,
+    Fetch,
+    Store,
+    PlusStore,
+    Allot,
+    Here,
     Num(Value),
 }
 
@@ -27,52 +54,345 @@ impl FromStr for Primitive {
             "drop" => Primitive::Drop,
             "swap" => Primitive::Swap,
             "over" => Primitive::Over,
-            _ => Primitive::Num(s.parse().map_err(|_| Error::InvalidWord)?),
+            "=" => Primitive::Eq,
+            "<" => Primitive::Lt,
+            ">" => Primitive::Gt,
+            "0=" => Primitive::ZeroEq,
+            "and" => Primitive::And,
+            "or" => Primitive::Or,
+            "invert" => Primitive::Invert,
+            "mod" => Primitive::Mod,
+            "/mod" => Primitive::DivMod,
+            "rot" => Primitive::Rot,
+            "-rot" => Primitive::MinusRot,
+            "nip" => Primitive::Nip,
+            "tuck" => Primitive::Tuck,
+            "pick" => Primitive::Pick,
+            "roll" => Primitive::Roll,
+            "2dup" => Primitive::TwoDup,
+            "2drop" => Primitive::TwoDrop,
+            "2swap" => Primitive::TwoSwap,
+            "." => Primitive::Dot,
+            "emit" => Primitive::Emit,
+            "cr" => Primitive::Cr,
+            ".s" => Primitive::DotS,
+            "@" => Primitive::Fetch,
+            "!" => Primitive::Store,
+            "+!" => Primitive::PlusStore,
+            "allot" => Primitive::Allot,
+            "here" => Primitive::Here,
+            _ => Primitive::Num(s.parse().map_err(
+                |err: std::num::ParseIntError| match err.kind() {
+                    IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => Error::Overflow,
+                    _ => Error::InvalidWord,
+                },
+            )?),
         })
     }
 }
 
-pub(crate) fn evaluate(stack: &mut Vec<Value>, primitive: Primitive) -> Result {
-    let mut pop = || stack.pop().ok_or(Error::StackUnderflow);
-    match primitive {
-        Primitive::Add => {
-            let (r, l) = (pop()?, pop()?);
-            stack.push(l + r);
-        }
-        Primitive::Sub => {
-            let (r, l) = (pop()?, pop()?);
-            stack.push(l - r);
-        }
-        Primitive::Mul => {
-            let (r, l) = (pop()?, pop()?);
-            stack.push(l * r);
-        }
-        Primitive::Div => {
-            let (r, l) = (pop()?, pop()?);
-            stack.push(l.checked_div(r).ok_or(Error::DivisionByZero)?);
-        }
-        Primitive::Dup => {
-            let v = pop()?;
-            stack.push(v);
-            stack.push(v);
-        }
-        Primitive::Drop => {
-            pop()?;
-        }
-        Primitive::Swap => {
-            (stack.len() >= 2)
-                .then(|| {
-                    let size = stack.len();
-                    stack.swap(size - 1, size - 2)
-                })
-                .ok_or(Error::StackUnderflow)?;
+impl fmt::Display for Primitive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let word = match self {
+            Primitive::Add => "+",
+            Primitive::Sub => "-",
+            Primitive::Mul => "*",
+            Primitive::Div => "/",
+            Primitive::Dup => "dup",
+            Primitive::Drop => "drop",
+            Primitive::Swap => "swap",
+            Primitive::Over => "over",
+            Primitive::Eq => "=",
+            Primitive::Lt => "<",
+            Primitive::Gt => ">",
+            Primitive::ZeroEq => "0=",
+            Primitive::And => "and",
+            Primitive::Or => "or",
+            Primitive::Invert => "invert",
+            Primitive::Mod => "mod",
+            Primitive::DivMod => "/mod",
+            Primitive::Rot => "rot",
+            Primitive::MinusRot => "-rot",
+            Primitive::Nip => "nip",
+            Primitive::Tuck => "tuck",
+            Primitive::Pick => "pick",
+            Primitive::Roll => "roll",
+            Primitive::TwoDup => "2dup",
+            Primitive::TwoDrop => "2drop",
+            Primitive::TwoSwap => "2swap",
+            Primitive::Dot => ".",
+            Primitive::Emit => "emit",
+            Primitive::Cr => "cr",
+            Primitive::DotS => ".s",
+            Primitive::Fetch => "@",
+            Primitive::Store => "!",
+            Primitive::PlusStore => "+!",
+            Primitive::Allot => "allot",
+            Primitive::Here => "here",
+            Primitive::Num(n) => return write!(f, "{}", n),
+        };
+        f.write_str(word)
+    }
+}
+
+/// Forth represents truth as all bits set.
+fn flag(condition: bool) -> Value {
+    if condition {
+        -1
+    } else {
+        0
+    }
+}
+
+/// Apply a checked division-like operation, distinguishing division by zero from overflow.
+fn divide(
+    l: Value,
+    r: Value,
+    op: fn(Value, Value) -> Option<Value>,
+) -> std::result::Result<Value, Error> {
+    if r == 0 {
+        return Err(Error::DivisionByZero);
+    }
+    op(l, r).ok_or(Error::Overflow)
+}
+
+/// Convert a stack value into an index from the top of a stack of the given size.
+fn depth(u: Value, size: usize) -> std::result::Result<usize, Error> {
+    usize::try_from(u)
+        .ok()
+        .filter(|&u| u < size)
+        .map(|u| size - 1 - u)
+        .ok_or(Error::StackUnderflow)
+}
+
+/// Look up the memory cell at the given address.
+fn cell(memory: &mut [Value], address: Value) -> std::result::Result<&mut Value, Error> {
+    usize::try_from(address)
+        .ok()
+        .and_then(move |address| memory.get_mut(address))
+        .ok_or(Error::InvalidAddress)
+}
+
+/// The state on which primitives operate.
+#[derive(Debug)]
+pub(crate) struct Machine<W> {
+    pub(crate) stack: Vec<Value>,
+    /// Cell-addressed memory, allotted by `variable` and `allot`.
+    pub(crate) memory: Vec<Value>,
+    /// The most cells `memory` may hold.
+    pub(crate) memory_limit: Option<usize>,
+    /// Where output words write.
+    pub(crate) output: W,
+}
+
+impl<W: Write> Machine<W> {
+    pub(crate) fn new(output: W) -> Self {
+        Machine {
+            stack: Vec::new(),
+            memory: Vec::new(),
+            memory_limit: None,
+            output,
         }
-        Primitive::Over => {
-            (stack.len() >= 2)
-                .then(|| stack.push(stack[stack.len() - 2]))
-                .ok_or(Error::StackUnderflow)?;
+    }
+
+    pub(crate) fn pop(&mut self) -> std::result::Result<Value, Error> {
+        self.stack.pop().ok_or(Error::StackUnderflow)
+    }
+
+    /// Write some text to the output.
+    pub(crate) fn print(&mut self, text: &str) -> Result {
+        self.output
+            .write_all(text.as_bytes())
+            .map_err(|err| Error::Io(err.kind()))
+    }
+
+    /// Evaluate a single primitive.
+    pub(crate) fn evaluate(&mut self, primitive: Primitive) -> Result {
+        let Machine {
+            stack,
+            memory,
+            memory_limit,
+            output,
+        } = self;
+        let io = |err: std::io::Error| Error::Io(err.kind());
+        let mut pop = || stack.pop().ok_or(Error::StackUnderflow);
+        match primitive {
+            Primitive::Add => {
+                let (r, l) = (pop()?, pop()?);
+                stack.push(l.checked_add(r).ok_or(Error::Overflow)?);
+            }
+            Primitive::Sub => {
+                let (r, l) = (pop()?, pop()?);
+                stack.push(l.checked_sub(r).ok_or(Error::Overflow)?);
+            }
+            Primitive::Mul => {
+                let (r, l) = (pop()?, pop()?);
+                stack.push(l.checked_mul(r).ok_or(Error::Overflow)?);
+            }
+            Primitive::Div => {
+                let (r, l) = (pop()?, pop()?);
+                stack.push(divide(l, r, Value::checked_div)?);
+            }
+            Primitive::Dup => {
+                let v = pop()?;
+                stack.push(v);
+                stack.push(v);
+            }
+            Primitive::Drop => {
+                pop()?;
+            }
+            Primitive::Swap => {
+                (stack.len() >= 2)
+                    .then(|| {
+                        let size = stack.len();
+                        stack.swap(size - 1, size - 2)
+                    })
+                    .ok_or(Error::StackUnderflow)?;
+            }
+            Primitive::Over => {
+                (stack.len() >= 2)
+                    .then(|| stack.push(stack[stack.len() - 2]))
+                    .ok_or(Error::StackUnderflow)?;
+            }
+            Primitive::Eq => {
+                let (r, l) = (pop()?, pop()?);
+                stack.push(flag(l == r));
+            }
+            Primitive::Lt => {
+                let (r, l) = (pop()?, pop()?);
+                stack.push(flag(l < r));
+            }
+            Primitive::Gt => {
+                let (r, l) = (pop()?, pop()?);
+                stack.push(flag(l > r));
+            }
+            Primitive::ZeroEq => {
+                let v = pop()?;
+                stack.push(flag(v == 0));
+            }
+            Primitive::And => {
+                let (r, l) = (pop()?, pop()?);
+                stack.push(l & r);
+            }
+            Primitive::Or => {
+                let (r, l) = (pop()?, pop()?);
+                stack.push(l | r);
+            }
+            Primitive::Invert => {
+                let v = pop()?;
+                stack.push(!v);
+            }
+            Primitive::Mod => {
+                let (r, l) = (pop()?, pop()?);
+                stack.push(divide(l, r, Value::checked_rem)?);
+            }
+            Primitive::DivMod => {
+                let (r, l) = (pop()?, pop()?);
+                stack.push(divide(l, r, Value::checked_rem)?);
+                stack.push(divide(l, r, Value::checked_div)?);
+            }
+            Primitive::Rot | Primitive::MinusRot => {
+                let size = stack.len();
+                (size >= 3)
+                    .then(|| {
+                        let top = &mut stack[size - 3..];
+                        if primitive == Primitive::Rot {
+                            top.rotate_left(1);
+                        } else {
+                            top.rotate_right(1);
+                        }
+                    })
+                    .ok_or(Error::StackUnderflow)?;
+            }
+            Primitive::Nip => {
+                let (r, _) = (pop()?, pop()?);
+                stack.push(r);
+            }
+            Primitive::Tuck => {
+                let (r, l) = (pop()?, pop()?);
+                stack.extend_from_slice(&[r, l, r]);
+            }
+            Primitive::Pick => {
+                let u = pop()?;
+                let idx = depth(u, stack.len())?;
+                stack.push(stack[idx]);
+            }
+            Primitive::Roll => {
+                let u = pop()?;
+                let idx = depth(u, stack.len())?;
+                stack[idx..].rotate_left(1);
+            }
+            Primitive::TwoDup => {
+                (stack.len() >= 2)
+                    .then(|| stack.extend_from_within(stack.len() - 2..))
+                    .ok_or(Error::StackUnderflow)?;
+            }
+            Primitive::TwoDrop => {
+                (stack.len() >= 2)
+                    .then(|| stack.truncate(stack.len() - 2))
+                    .ok_or(Error::StackUnderflow)?;
+            }
+            Primitive::TwoSwap => {
+                let size = stack.len();
+                (size >= 4)
+                    .then(|| stack[size - 4..].rotate_left(2))
+                    .ok_or(Error::StackUnderflow)?;
+            }
+            Primitive::Dot => {
+                let v = pop()?;
+                write!(output, "{} ", v).map_err(io)?;
+            }
+            Primitive::Emit => {
+                let v = pop()?;
+                let c = u32::try_from(v)
+                    .ok()
+                    .and_then(std::char::from_u32)
+                    .unwrap_or(std::char::REPLACEMENT_CHARACTER);
+                write!(output, "{}", c).map_err(io)?;
+            }
+            Primitive::Cr => writeln!(output).map_err(io)?,
+            Primitive::DotS => {
+                write!(output, "<{}> ", stack.len()).map_err(io)?;
+                for v in stack.iter() {
+                    write!(output, "{} ", v).map_err(io)?;
+                }
+            }
+            Primitive::Fetch => {
+                let address = pop()?;
+                let v = *cell(memory, address)?;
+                stack.push(v);
+            }
+            Primitive::Store => {
+                let (address, v) = (pop()?, pop()?);
+                *cell(memory, address)? = v;
+            }
+            Primitive::PlusStore => {
+                let (address, v) = (pop()?, pop()?);
+                let cell = cell(memory, address)?;
+                *cell = cell.checked_add(v).ok_or(Error::Overflow)?;
+            }
+            Primitive::Allot => {
+                let n = pop()?;
+                let size = Value::try_from(memory.len())
+                    .ok()
+                    .and_then(|size| size.checked_add(n))
+                    .ok_or(Error::Overflow)?;
+                let size = usize::try_from(size).map_err(|_| Error::InvalidAddress)?;
+                if memory_limit.is_some_and(|limit| size > limit) {
+                    return Err(Error::MemoryLimitExceeded);
+                }
+                // an unlimited machine can still fail gracefully if allocation does
+                memory
+                    .try_reserve(size.saturating_sub(memory.len()))
+                    .map_err(|_| Error::MemoryLimitExceeded)?;
+                memory.resize(size, 0);
+            }
+            Primitive::Here => {
+                let size = Value::try_from(memory.len()).map_err(|_| Error::Overflow)?;
+                stack.push(size);
+            }
+            Primitive::Num(n) => stack.push(n),
         }
-        Primitive::Num(n) => stack.push(n),
+        Ok(())
     }
-    Ok(())
 }
@@ -0,0 +1,44 @@
+//! An interactive Forth interpreter.
+//!
+//! Each line read is evaluated against the same interpreter, after which the stack
+//! is printed. Evaluation is atomic: a line which fails has no effect. Each line
+//! is limited in how much work it may do, so runaway programs can't hang the REPL.
+//!
+//! Requires the `repl` feature: `cargo run --features repl`.
+
+use forth::{Forth, Limits};
+use rustyline::{error::ReadlineError, DefaultEditor};
+
+fn main() -> rustyline::Result<()> {
+    let mut forth = Forth::with_output(std::io::stdout());
+    forth.set_atomic(true);
+    forth.set_limits(Limits {
+        steps: Some(100_000_000),
+        depth: Some(1024),
+        memory: Some(1 << 20),
+    });
+    let mut editor = DefaultEditor::new()?;
+
+    loop {
+        let line = match editor.readline("> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(err),
+        };
+        editor.add_history_entry(line.as_str())?;
+
+        match forth.eval_diagnostic(&line) {
+            Ok(()) => {
+                print!(" <{}>", forth.stack().len());
+                for value in forth.stack() {
+                    print!(" {}", value);
+                }
+                println!(" ok");
+            }
+            Err(diagnostic) => eprintln!("error: {}", diagnostic),
+        }
+    }
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use forth::{Error, Forth, Value};
+use forth::{Diagnostic, Error, Forth, Limits, Value};
 
 #[test]
 fn no_input_no_stack() {
@@ -371,3 +371,800 @@ fn redefine_an_existing_word_with_another_existing_word() {
     assert!(f.eval("bar foo").is_ok());
     assert_eq!(vec![6, 6], f.stack());
 }
+
+// Control flow
+
+#[test]
+#[ignore]
+fn if_then() {
+    let mut f = Forth::new();
+    assert!(f.eval(": f if 1 then 2 ;").is_ok());
+    assert!(f.eval("-1 f 0 f").is_ok());
+    assert_eq!(vec![1, 2, 2], f.stack());
+}
+
+#[test]
+#[ignore]
+fn if_else_then() {
+    let mut f = Forth::new();
+    assert!(f.eval(": sign dup 0 swap - if 1 else 2 then ;").is_ok());
+    assert!(f.eval("5 sign 0 sign").is_ok());
+    assert_eq!(vec![5, 1, 0, 2], f.stack());
+}
+
+#[test]
+#[ignore]
+fn nested_if() {
+    let mut f = Forth::new();
+    assert!(f.eval(": f if if 1 else 2 then else 3 then ;").is_ok());
+    assert!(f.eval("1 1 f 0 1 f 0 f").is_ok());
+    assert_eq!(vec![1, 2, 3], f.stack());
+}
+
+#[test]
+#[ignore]
+fn do_loop() {
+    let mut f = Forth::new();
+    assert!(f.eval(": count 0 do i loop ;").is_ok());
+    assert!(f.eval("4 count").is_ok());
+    assert_eq!(vec![0, 1, 2, 3], f.stack());
+}
+
+#[test]
+#[ignore]
+fn do_plus_loop() {
+    let mut f = Forth::new();
+    assert!(f.eval(": evens 10 0 do i 2 +loop ;").is_ok());
+    assert!(f.eval(": down 0 3 do i -1 +loop ;").is_ok());
+    assert!(f.eval("evens down").is_ok());
+    assert_eq!(vec![0, 2, 4, 6, 8, 3, 2, 1, 0], f.stack());
+}
+
+#[test]
+#[ignore]
+fn nested_do_loop() {
+    let mut f = Forth::new();
+    assert!(f.eval(": grid 2 0 do 2 0 do j i loop loop ;").is_ok());
+    assert!(f.eval("grid").is_ok());
+    assert_eq!(vec![0, 0, 0, 1, 1, 0, 1, 1], f.stack());
+}
+
+#[test]
+#[ignore]
+fn begin_until() {
+    let mut f = Forth::new();
+    assert!(f.eval(": upto-3 begin dup 1 + dup 3 / until ;").is_ok());
+    assert!(f.eval("0 upto-3").is_ok());
+    assert_eq!(vec![0, 1, 2, 3], f.stack());
+}
+
+#[test]
+#[ignore]
+fn begin_while_repeat() {
+    let mut f = Forth::new();
+    assert!(f
+        .eval(": countdown begin dup while dup 1 - repeat ;")
+        .is_ok());
+    assert!(f.eval("2 countdown").is_ok());
+    assert_eq!(vec![2, 1, 0], f.stack());
+}
+
+#[test]
+#[ignore]
+fn control_flow_calls_other_words() {
+    let mut f = Forth::new();
+    assert!(f.eval(": sq dup * ;").is_ok());
+    assert!(f.eval(": squares 0 do i sq loop ;").is_ok());
+    assert!(f.eval("3 squares").is_ok());
+    assert_eq!(vec![0, 1, 4], f.stack());
+}
+
+#[test]
+#[ignore]
+fn control_flow_outside_definition() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::InvalidWord), f.eval("1 if 2 then"));
+}
+
+#[test]
+#[ignore]
+fn unbalanced_control_flow() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::InvalidWord), f.eval(": foo 1 if 2 ;"));
+    assert_eq!(Err(Error::InvalidWord), f.eval(": foo then ;"));
+    assert_eq!(Err(Error::InvalidWord), f.eval(": foo do if loop then ;"));
+    assert_eq!(Err(Error::InvalidWord), f.eval(": foo i ;"));
+    assert_eq!(Err(Error::InvalidWord), f.eval(": foo 1 0 do j loop ;"));
+}
+
+#[test]
+#[ignore]
+fn conditional_error() {
+    let mut f = Forth::new();
+    assert!(f.eval(": foo if 1 then ;").is_ok());
+    assert_eq!(Err(Error::StackUnderflow), f.eval("foo"));
+}
+
+// Comparison and logic
+
+#[test]
+#[ignore]
+fn comparisons() {
+    let mut f = Forth::new();
+    assert!(f.eval("1 1 = 1 2 = 1 2 < 2 1 < 2 1 > 1 2 >").is_ok());
+    assert_eq!(vec![-1, 0, -1, 0, -1, 0], f.stack());
+}
+
+#[test]
+#[ignore]
+fn zero_equals() {
+    let mut f = Forth::new();
+    assert!(f.eval("0 0= 5 0=").is_ok());
+    assert_eq!(vec![-1, 0], f.stack());
+}
+
+#[test]
+#[ignore]
+fn bitwise_logic() {
+    let mut f = Forth::new();
+    assert!(f.eval("12 10 and 12 10 or 0 invert").is_ok());
+    assert_eq!(vec![8, 14, -1], f.stack());
+}
+
+#[test]
+#[ignore]
+fn comparison_error() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::StackUnderflow), f.eval("1 ="));
+    assert_eq!(Err(Error::StackUnderflow), f.eval("0="));
+    assert_eq!(Err(Error::StackUnderflow), f.eval("invert"));
+}
+
+#[test]
+#[ignore]
+fn modulo() {
+    let mut f = Forth::new();
+    assert!(f.eval("13 4 mod 13 4 /mod").is_ok());
+    assert_eq!(vec![1, 1, 3], f.stack());
+}
+
+#[test]
+#[ignore]
+fn modulo_by_zero() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::DivisionByZero), f.eval("4 0 mod"));
+    assert_eq!(Err(Error::DivisionByZero), f.eval("4 0 /mod"));
+}
+
+// Extended stack manipulation
+
+#[test]
+#[ignore]
+fn rot() {
+    let mut f = Forth::new();
+    assert!(f.eval("1 2 3 rot").is_ok());
+    assert_eq!(vec![2, 3, 1], f.stack());
+    assert!(f.eval("-rot").is_ok());
+    assert_eq!(vec![1, 2, 3], f.stack());
+}
+
+#[test]
+#[ignore]
+fn rot_error() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::StackUnderflow), f.eval("1 2 rot"));
+    assert_eq!(Err(Error::StackUnderflow), f.eval("-rot"));
+}
+
+#[test]
+#[ignore]
+fn nip_and_tuck() {
+    let mut f = Forth::new();
+    assert!(f.eval("1 2 nip 3 tuck").is_ok());
+    assert_eq!(vec![3, 2, 3], f.stack());
+}
+
+#[test]
+#[ignore]
+fn nip_and_tuck_error() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::StackUnderflow), f.eval("1 nip"));
+    assert_eq!(Err(Error::StackUnderflow), f.eval("tuck"));
+}
+
+#[test]
+#[ignore]
+fn pick() {
+    let mut f = Forth::new();
+    assert!(f.eval("1 2 3 0 pick 3 pick").is_ok());
+    assert_eq!(vec![1, 2, 3, 3, 1], f.stack());
+}
+
+#[test]
+#[ignore]
+fn roll() {
+    let mut f = Forth::new();
+    assert!(f.eval("1 2 3 4 2 roll").is_ok());
+    assert_eq!(vec![1, 3, 4, 2], f.stack());
+    assert!(f.eval("0 roll").is_ok());
+    assert_eq!(vec![1, 3, 4, 2], f.stack());
+}
+
+#[test]
+#[ignore]
+fn pick_and_roll_error() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::StackUnderflow), f.eval("1 1 pick"));
+    assert_eq!(Err(Error::StackUnderflow), f.eval("1 -1 roll"));
+    assert_eq!(Err(Error::StackUnderflow), f.eval("pick"));
+}
+
+#[test]
+#[ignore]
+fn double_cell_words() {
+    let mut f = Forth::new();
+    assert!(f.eval("1 2 2dup 3 4 2swap 2drop").is_ok());
+    assert_eq!(vec![1, 2, 3, 4], f.stack());
+}
+
+#[test]
+#[ignore]
+fn double_cell_words_error() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::StackUnderflow), f.eval("1 2dup"));
+    assert_eq!(Err(Error::StackUnderflow), f.eval("2drop"));
+    assert_eq!(Err(Error::StackUnderflow), f.eval("1 2 2swap"));
+}
+
+// Overflow
+
+#[test]
+#[ignore]
+fn addition_overflow() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::Overflow), f.eval(&format!("{} 1 +", Value::MAX)));
+}
+
+#[test]
+#[ignore]
+fn subtraction_overflow() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::Overflow), f.eval(&format!("{} 1 -", Value::MIN)));
+}
+
+#[test]
+#[ignore]
+fn multiplication_overflow() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::Overflow), f.eval(&format!("{} 2 *", Value::MAX)));
+}
+
+#[test]
+#[ignore]
+fn division_overflow() {
+    let mut f = Forth::new();
+    assert_eq!(
+        Err(Error::Overflow),
+        f.eval(&format!("{} -1 /", Value::MIN))
+    );
+    assert_eq!(
+        Err(Error::Overflow),
+        f.eval(&format!("{} -1 mod", Value::MIN))
+    );
+}
+
+#[test]
+#[ignore]
+fn number_too_large_for_a_cell() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::Overflow), f.eval(&format!("{}0", Value::MAX)));
+}
+
+#[test]
+#[ignore]
+fn defining_a_number_too_large_for_a_cell() {
+    let mut f = Forth::new();
+    assert_eq!(
+        Err(Error::InvalidWord),
+        f.eval(&format!(": {}0 1 ;", Value::MAX))
+    );
+    assert_eq!(
+        Err(Error::InvalidWord),
+        f.eval(&format!(": {}0 1 ;", Value::MIN))
+    );
+}
+
+#[test]
+#[ignore]
+fn extreme_values_without_overflow() {
+    let mut f = Forth::new();
+    assert!(f
+        .eval(&format!("{} 0 + {} 0 -", Value::MAX, Value::MIN))
+        .is_ok());
+    assert_eq!(vec![Value::MAX, Value::MIN], f.stack());
+}
+
+// Output
+
+#[test]
+#[ignore]
+fn dot_prints_and_pops() {
+    let mut f = Forth::new();
+    assert!(f.eval("1 2 . .").is_ok());
+    assert_eq!(b"2 1 ", f.output().as_slice());
+    assert_eq!(Vec::<Value>::new(), f.stack());
+}
+
+#[test]
+#[ignore]
+fn dot_error() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::StackUnderflow), f.eval("."));
+}
+
+#[test]
+#[ignore]
+fn emit_and_cr() {
+    let mut f = Forth::new();
+    assert!(f.eval("72 emit 105 emit cr").is_ok());
+    assert_eq!(b"Hi\n", f.output().as_slice());
+}
+
+#[test]
+#[ignore]
+fn dot_s_does_not_modify_stack() {
+    let mut f = Forth::new();
+    assert!(f.eval("1 2 3 .s").is_ok());
+    assert_eq!(b"<3> 1 2 3 ", f.output().as_slice());
+    assert_eq!(vec![1, 2, 3], f.stack());
+}
+
+#[test]
+#[ignore]
+fn dot_quote() {
+    let mut f = Forth::new();
+    assert!(f.eval(r#"." Hello,  World!" 1"#).is_ok());
+    assert_eq!(b"Hello,  World!", f.output().as_slice());
+    assert_eq!(vec![1], f.stack());
+}
+
+#[test]
+#[ignore]
+fn dot_quote_in_definition() {
+    let mut f = Forth::new();
+    assert!(f.eval(r#": greet ." hi " . ;"#).is_ok());
+    assert!(f.eval("1 greet 2 greet").is_ok());
+    assert_eq!(b"hi 1 hi 2 ", f.output().as_slice());
+}
+
+#[test]
+#[ignore]
+fn unterminated_dot_quote() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::InvalidWord), f.eval(r#"." oops"#));
+}
+
+#[test]
+#[ignore]
+fn custom_output_sink() {
+    let mut out = Vec::new();
+    {
+        let mut f = Forth::with_output(&mut out);
+        assert!(f.eval("3 4 + .").is_ok());
+    }
+    assert_eq!(b"7 ", out.as_slice());
+}
+
+// Variables, constants and memory
+
+#[test]
+#[ignore]
+fn variable_store_and_fetch() {
+    let mut f = Forth::new();
+    assert!(f.eval("variable x 42 x ! x @").is_ok());
+    assert_eq!(vec![42], f.stack());
+}
+
+#[test]
+#[ignore]
+fn variables_start_at_zero() {
+    let mut f = Forth::new();
+    assert!(f.eval("variable x x @").is_ok());
+    assert_eq!(vec![0], f.stack());
+}
+
+#[test]
+#[ignore]
+fn variables_persist_between_evals() {
+    let mut f = Forth::new();
+    assert!(f.eval("variable counter").is_ok());
+    assert!(f.eval(": bump 1 counter +! ;").is_ok());
+    assert!(f.eval("bump bump").is_ok());
+    assert!(f.eval("bump counter @").is_ok());
+    assert_eq!(vec![3], f.stack());
+}
+
+#[test]
+#[ignore]
+fn distinct_variables() {
+    let mut f = Forth::new();
+    assert!(f.eval("variable a variable b 1 a ! 2 b ! a @ b @").is_ok());
+    assert_eq!(vec![1, 2], f.stack());
+}
+
+#[test]
+#[ignore]
+fn constant() {
+    let mut f = Forth::new();
+    assert!(f.eval("7 constant seven seven seven *").is_ok());
+    assert_eq!(vec![49], f.stack());
+}
+
+#[test]
+#[ignore]
+fn constant_error() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::StackUnderflow), f.eval("constant nothing"));
+}
+
+#[test]
+#[ignore]
+fn allot_extends_a_variable_into_an_array() {
+    let mut f = Forth::new();
+    assert!(f.eval("variable arr 2 allot").is_ok());
+    assert!(f.eval("10 arr ! 20 arr 1 + ! 30 arr 2 + !").is_ok());
+    assert!(f.eval("arr @ arr 1 + @ arr 2 + @").is_ok());
+    assert_eq!(vec![10, 20, 30], f.stack());
+}
+
+#[test]
+#[ignore]
+fn invalid_address() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::InvalidAddress), f.eval("0 @"));
+    assert!(f.eval("variable x").is_ok());
+    assert_eq!(Err(Error::InvalidAddress), f.eval("1 x 1 + !"));
+    assert_eq!(Err(Error::InvalidAddress), f.eval("-1 @"));
+}
+
+#[test]
+#[ignore]
+fn defining_words_need_a_name() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::InvalidWord), f.eval("variable"));
+    assert_eq!(Err(Error::InvalidWord), f.eval("1 constant 2"));
+}
+
+#[test]
+#[ignore]
+fn defining_words_in_a_definition() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::InvalidWord), f.eval(": foo variable x ;"));
+}
+
+// Diagnostics
+
+#[test]
+#[ignore]
+fn diagnostic_locates_the_failing_word() {
+    let mut f = Forth::new();
+    assert_eq!(
+        Err(Diagnostic {
+            error: Error::UnknownWord,
+            word: "FOO".to_string(),
+            offset: 4,
+            call_chain: Vec::new(),
+        }),
+        f.eval_diagnostic("1 2 FOO 3")
+    );
+}
+
+#[test]
+#[ignore]
+fn diagnostic_includes_the_call_chain() {
+    let mut f = Forth::new();
+    assert!(f
+        .eval(": inner + ; : inner inner ; : outer inner ;")
+        .is_ok());
+    assert_eq!(
+        Err(Diagnostic {
+            error: Error::StackUnderflow,
+            word: "outer".to_string(),
+            offset: 2,
+            call_chain: vec![
+                ("outer".to_string(), 0),
+                ("inner".to_string(), 1),
+                ("inner".to_string(), 0),
+            ],
+        }),
+        f.eval_diagnostic("1 outer")
+    );
+}
+
+#[test]
+#[ignore]
+fn diagnostic_call_chain_excludes_completed_words() {
+    let mut f = Forth::new();
+    assert!(f.eval(": ok 1 ;").is_ok());
+    let diagnostic = f.eval_diagnostic("ok ok 0 /").unwrap_err();
+    assert_eq!(Error::DivisionByZero, diagnostic.error);
+    assert_eq!("/", diagnostic.word);
+    assert_eq!(8, diagnostic.offset);
+    assert!(diagnostic.call_chain.is_empty());
+}
+
+#[test]
+#[ignore]
+fn diagnostic_at_end_of_input() {
+    let mut f = Forth::new();
+    let diagnostic = f.eval_diagnostic(": foo 1").unwrap_err();
+    assert_eq!(Error::InvalidWord, diagnostic.error);
+    assert_eq!("", diagnostic.word);
+    assert_eq!(7, diagnostic.offset);
+}
+
+#[test]
+#[ignore]
+fn diagnostic_display() {
+    let mut f = Forth::new();
+    assert!(f.eval(": foo drop ;").is_ok());
+    assert_eq!(
+        "stack underflow at `foo` (byte 0), in foo (version 0)",
+        f.eval_diagnostic("foo").unwrap_err().to_string()
+    );
+    assert_eq!(
+        "invalid word at end of input",
+        f.eval_diagnostic(":").unwrap_err().to_string()
+    );
+}
+
+// Atomic evaluation
+
+#[test]
+#[ignore]
+fn non_atomic_eval_keeps_partial_effects() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::UnknownWord), f.eval("1 2 : foo 3 ; bar"));
+    assert_eq!(vec![1, 2], f.stack());
+    assert!(f.eval("foo").is_ok());
+}
+
+#[test]
+#[ignore]
+fn atomic_eval_restores_the_stack() {
+    let mut f = Forth::new();
+    f.set_atomic(true);
+    assert!(f.eval("1 2").is_ok());
+    assert_eq!(Err(Error::StackUnderflow), f.eval("+ + 3"));
+    assert_eq!(vec![1, 2], f.stack());
+}
+
+#[test]
+#[ignore]
+fn atomic_eval_restores_definitions() {
+    let mut f = Forth::new();
+    f.set_atomic(true);
+    assert!(f.eval(": foo 1 ;").is_ok());
+    assert_eq!(
+        Err(Error::UnknownWord),
+        f.eval(": foo 2 ; : bar 3 ; 5 constant five baz")
+    );
+    assert_eq!(Err(Error::UnknownWord), f.eval("bar"));
+    assert_eq!(Err(Error::UnknownWord), f.eval("five"));
+    assert!(f.eval("foo").is_ok());
+    assert_eq!(vec![1], f.stack());
+}
+
+#[test]
+#[ignore]
+fn atomic_eval_restores_memory() {
+    let mut f = Forth::new();
+    f.set_atomic(true);
+    assert!(f.eval("variable x 1 x !").is_ok());
+    assert_eq!(Err(Error::DivisionByZero), f.eval("2 x ! variable y 1 0 /"));
+    assert!(f.eval("x @ here").is_ok());
+    assert_eq!(vec![1, 1], f.stack());
+}
+
+#[test]
+#[ignore]
+fn atomic_eval_keeps_successful_effects() {
+    let mut f = Forth::new();
+    f.set_atomic(true);
+    assert!(f.eval("1 : foo 2 ; foo").is_ok());
+    assert_eq!(vec![1, 2], f.stack());
+}
+
+// Introspection
+
+#[test]
+#[ignore]
+fn words_lists_custom_words() {
+    let mut f = Forth::new();
+    assert!(f.eval(": foo 1 ; variable bar : foo 2 ;").is_ok());
+    assert!(f.eval("words").is_ok());
+    assert_eq!(b"bar foo ", f.output().as_slice());
+}
+
+#[test]
+#[ignore]
+fn see_shows_the_latest_version() {
+    let mut f = Forth::new();
+    assert!(f.eval(": foo 1 ; : foo foo dup + ;").is_ok());
+    assert!(f.eval("see foo").is_ok());
+    assert_eq!(
+        ": foo (version 1)\n   0: foo#0\n   1: dup\n   2: +\n;\n",
+        String::from_utf8_lossy(f.output())
+    );
+}
+
+#[test]
+#[ignore]
+fn see_shows_compiled_control_flow() {
+    let mut f = Forth::new();
+    assert!(f.eval(r#": foo if ." yes" else 0 then ;"#).is_ok());
+    assert!(f.eval("see foo").is_ok());
+    assert_eq!(
+        ": foo (version 0)\n   0: 0branch -> 3\n   1: .\" yes\"\n   2: branch -> 4\n   3: 0\n;\n",
+        String::from_utf8_lossy(f.output())
+    );
+}
+
+#[test]
+#[ignore]
+fn see_unknown_word() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::UnknownWord), f.eval("see dup"));
+}
+
+#[test]
+#[ignore]
+fn forget_restores_the_previous_version() {
+    let mut f = Forth::new();
+    assert!(f.eval(": foo 1 ; : foo 2 ;").is_ok());
+    assert!(f.eval("forget foo foo").is_ok());
+    assert_eq!(vec![1], f.stack());
+    assert!(f.eval("forget foo").is_ok());
+    assert_eq!(Err(Error::UnknownWord), f.eval("foo"));
+}
+
+#[test]
+#[ignore]
+fn forget_referenced_word() {
+    let mut f = Forth::new();
+    assert!(f.eval(": foo 1 ; : bar foo ;").is_ok());
+    assert_eq!(Err(Error::InvalidWord), f.eval("forget foo"));
+    assert!(f.eval("forget bar forget foo").is_ok());
+}
+
+#[test]
+#[ignore]
+fn forget_unknown_word() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::UnknownWord), f.eval("forget foo"));
+}
+
+#[test]
+#[ignore]
+fn atomic_eval_restores_forgotten_words() {
+    let mut f = Forth::new();
+    f.set_atomic(true);
+    assert!(f.eval(": foo 1 ;").is_ok());
+    assert_eq!(Err(Error::UnknownWord), f.eval("forget foo bar"));
+    assert!(f.eval("foo").is_ok());
+    assert_eq!(vec![1], f.stack());
+}
+
+// Limits
+
+#[test]
+#[ignore]
+fn infinite_loop_exceeds_step_limit() {
+    let mut f = Forth::new();
+    f.set_limits(Limits {
+        steps: Some(1000),
+        ..Limits::default()
+    });
+    assert!(f.eval(": forever begin again ;").is_ok());
+    assert_eq!(Err(Error::StepLimitExceeded), f.eval("forever"));
+}
+
+#[test]
+#[ignore]
+fn step_limit_applies_per_eval() {
+    let mut f = Forth::new();
+    f.set_limits(Limits {
+        steps: Some(3),
+        ..Limits::default()
+    });
+    assert!(f.eval("1 2 +").is_ok());
+    assert!(f.eval("3 4 +").is_ok());
+    assert_eq!(Err(Error::StepLimitExceeded), f.eval("1 1 1 1"));
+}
+
+#[test]
+#[ignore]
+fn exponential_expansion_exceeds_step_limit() {
+    let mut f = Forth::new();
+    f.set_limits(Limits {
+        steps: Some(100_000),
+        ..Limits::default()
+    });
+    assert!(f.eval(": a 1 drop ;").is_ok());
+    for _ in 0..30 {
+        assert!(f.eval(": a a a ;").is_ok());
+    }
+    assert_eq!(Err(Error::StepLimitExceeded), f.eval("a"));
+}
+
+#[test]
+#[ignore]
+fn deep_nesting_exceeds_recursion_limit() {
+    let mut f = Forth::new();
+    f.set_limits(Limits {
+        depth: Some(10),
+        ..Limits::default()
+    });
+    assert!(f.eval(": a 1 ;").is_ok());
+    for _ in 0..9 {
+        assert!(f.eval(": a a ;").is_ok());
+    }
+    assert!(f.eval("a").is_ok());
+    assert!(f.eval(": a a ;").is_ok());
+    assert_eq!(Err(Error::RecursionLimit), f.eval("a"));
+}
+
+#[test]
+#[ignore]
+fn allot_exceeds_memory_limit() {
+    let mut f = Forth::new();
+    f.set_limits(Limits {
+        memory: Some(4),
+        ..Limits::default()
+    });
+    assert!(f.eval("variable x 3 allot").is_ok());
+    assert_eq!(Err(Error::MemoryLimitExceeded), f.eval("1 allot"));
+    assert_eq!(Err(Error::MemoryLimitExceeded), f.eval("variable y"));
+    assert_eq!(Err(Error::MemoryLimitExceeded), f.eval("1000000000 allot"));
+    assert!(f.eval("here").is_ok());
+    assert_eq!(vec![4], f.stack());
+}
+
+#[test]
+#[ignore]
+fn memory_limited_by_default() {
+    let mut f = Forth::new();
+    assert_eq!(
+        Err(Error::MemoryLimitExceeded),
+        f.eval(&format!("{} allot", Value::MAX))
+    );
+    let mut f = Forth::with_output(Vec::new());
+    assert!(f.eval("1000 allot").is_ok());
+    assert_eq!(
+        Err(Error::MemoryLimitExceeded),
+        f.eval(&format!("{} allot", Value::MAX - 1000))
+    );
+}
+
+#[test]
+#[ignore]
+fn unlimited_by_default() {
+    let mut f = Forth::new();
+    assert!(f.eval(": a 1 drop ;").is_ok());
+    for _ in 0..12 {
+        assert!(f.eval(": a a a ;").is_ok());
+    }
+    assert!(f.eval("a").is_ok());
+}
+
+#[test]
+#[ignore]
+fn deep_nesting_does_not_exhaust_native_stack() {
+    let mut f = Forth::new();
+    assert!(f.eval(": a 1 ;").is_ok());
+    for _ in 0..100_000 {
+        assert!(f.eval(": a a ;").is_ok());
+    }
+    assert!(f.eval("a").is_ok());
+    assert_eq!(vec![1], f.stack());
+}
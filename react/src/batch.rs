@@ -0,0 +1,40 @@
+use crate::{id::InputCellID, value::Value, Reactor};
+
+/// A set of updates to a reactor's input cells, to be propagated together.
+///
+/// See `Reactor::batch`.
+pub struct Batch<'r, 'a> {
+    reactor: &'r mut Reactor<'a>,
+    /// Each input cell which was set, with its value from before the batch.
+    changed: Vec<(usize, Box<dyn Value>)>,
+}
+
+impl<'r, 'a> Batch<'r, 'a> {
+    pub(crate) fn new(reactor: &'r mut Reactor<'a>) -> Self {
+        Self {
+            reactor,
+            changed: Vec::new(),
+        }
+    }
+
+    /// Sets the value of the specified input cell. The change is not propagated to compute
+    /// cells until the batch ends.
+    ///
+    /// Returns false if the cell does not exist.
+    pub fn set_value<T: 'static + PartialEq>(&mut self, id: InputCellID<T>, new_value: T) -> bool {
+        let previous = match self.reactor.assign(id, new_value) {
+            None => return false,
+            Some(previous) => previous,
+        };
+        // only the value from before the batch matters
+        if self.changed.iter().all(|(idx, _)| *idx != id.0) {
+            self.changed.push((id.0, Box::new(previous)));
+        }
+        true
+    }
+
+    /// The input cells which were set during the batch, with their previous values.
+    pub(crate) fn into_changed(self) -> Vec<(usize, Box<dyn Value>)> {
+        self.changed
+    }
+}
@@ -1,4 +1,7 @@
-pub type Callback<'a, T> = Box<dyn 'a + FnMut(T)>;
+use crate::value::Value;
+
+/// A type-erased callback, called with a cell's previous value and its new one.
+pub(crate) type Callback<'a> = Box<dyn 'a + FnMut(&dyn Value, &dyn Value)>;
 
 #[derive(Debug, PartialEq)]
 pub enum RemoveCallbackError {
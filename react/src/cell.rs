@@ -1,100 +1,234 @@
 use crate::{
-    id::{CallbackID, CellID, ComputeCellID},
-    Reactor,
+    dependencies::Dependencies,
+    error::CellError,
+    id::{AnyCellID, CallbackID},
+    value::Value,
+};
+use std::{
+    any::TypeId,
+    cell::{Ref, RefCell},
 };
 
-pub(crate) struct InputCell<T> {
-    pub(crate) value: T,
-    pub(crate) fwd: Vec<ComputeCellID>,
+pub(crate) struct InputCell {
+    pub(crate) value: RefCell<Box<dyn Value>>,
+    pub(crate) fwd: Vec<usize>,
+    pub(crate) callbacks: Vec<CallbackID>,
 }
 
-impl<T> InputCell<T> {
-    pub(crate) fn new(value: T) -> Self {
+impl InputCell {
+    pub(crate) fn new(value: Box<dyn Value>) -> Self {
         Self {
-            value,
+            value: RefCell::new(value),
             fwd: Vec::new(),
+            callbacks: Vec::new(),
         }
     }
 }
 
-pub(crate) struct ComputeCell<T> {
-    dependencies: Vec<CellID>,
-    computation: Box<dyn Fn(&[T]) -> T>,
-    pub(crate) cache: T,
-    fwd: Vec<ComputeCellID>,
+type Computation = Box<dyn Fn(&[&dyn Value]) -> Box<dyn Value>>;
+
+/// Collects the values of a compute cell's dependencies.
+type Extract<V> = fn(&[&dyn Value]) -> Result<V, CellError>;
+
+pub(crate) struct ComputeCell {
+    dependencies: Vec<usize>,
+    computation: Computation,
+    // the cache is only mutated through a shared reference when a lazy cell is read.
+    // it's empty until a lazy cell is first read.
+    cache: RefCell<Option<Box<dyn Value>>>,
+    value_type: TypeId,
+    /// Lazy cells aren't recomputed when their dependencies change, only when they're read.
+    lazy: bool,
+    /// Whether the cache is out of date. Only lazy cells are ever dirty.
+    dirty: std::cell::Cell<bool>,
+    fwd: Vec<usize>,
     pub(crate) callbacks: Vec<CallbackID>,
 }
 
-impl<T> ComputeCell<T>
-where
-    T: Copy + PartialEq,
-{
+impl ComputeCell {
     /// caution: use only when you know all dependencies are legal
-    fn calculate<F>(cells: &[Cell<T>], dependencies: &[CellID], computation: F) -> T
-    where
-        F: Fn(&[T]) -> T,
-    {
-        let values: Vec<T> = dependencies
+    fn calculate(
+        cells: &[Option<Cell>],
+        dependencies: &[usize],
+        computation: &Computation,
+    ) -> Box<dyn Value> {
+        let values: Vec<Ref<'_, dyn Value>> = dependencies
             .iter()
-            .map(|cid| cells[cid.idx()].value())
+            .map(|&idx| Cell::read(cells, idx))
             .collect();
+        let values: Vec<&dyn Value> = values.iter().map(|value| &**value).collect();
         computation(&values)
     }
 
-    pub(crate) fn new<F>(
-        reactor: &Reactor<T>,
-        dependencies: &[CellID],
+    /// The computation is given the values of the dependencies, or the first error among
+    /// them.
+    pub(crate) fn new<D, F, U>(
+        cells: &[Option<Cell>],
+        dependencies: D,
         computation: F,
-    ) -> Result<Self, CellID>
+        lazy: bool,
+    ) -> Result<Self, AnyCellID>
     where
-        F: 'static + Fn(&[T]) -> T,
+        D: Dependencies,
+        F: 'static + Fn(Result<D::Values, CellError>) -> U,
+        U: 'static + PartialEq,
     {
-        // ensure that all dependencies are legal
-        if let Some(missing) = dependencies
-            .iter()
-            .find(|id| id.idx() >= reactor.cells.len())
-        {
+        // ensure that all dependencies are legal: they exist, and hold the right type
+        let ids = dependencies.ids();
+        if let Some((missing, _)) = ids.iter().find(|(id, type_id)| {
+            cells
+                .get(id.0)
+                .and_then(Option::as_ref)
+                .is_none_or(|cell| cell.value_type() != *type_id)
+        }) {
             return Err(*missing);
         }
 
+        // a fn pointer doesn't borrow the dependencies, so the computation can outlive them
+        let values: Extract<D::Values> = D::values;
+        let computation: Computation =
+            Box::new(move |dependencies| Box::new(computation(values(dependencies))));
+        let dependencies: Vec<usize> = ids.iter().map(|(id, _)| id.0).collect();
+
+        // lazy cells don't compute anything until they're first read
+        let cache = if lazy {
+            None
+        } else {
+            Some(Self::calculate(cells, &dependencies, &computation))
+        };
+
         Ok(Self {
-            dependencies: dependencies.to_owned(),
-            cache: Self::calculate(&reactor.cells, dependencies, &computation),
-            computation: Box::new(computation),
+            cache: RefCell::new(cache),
+            value_type: TypeId::of::<U>(),
+            dependencies,
+            computation,
+            lazy,
+            dirty: std::cell::Cell::new(lazy),
             fwd: Vec::new(),
             callbacks: Vec::new(),
         })
     }
 
-    pub(crate) fn recompute(&mut self, cells: &[Cell<T>]) {
-        self.cache = Self::calculate(cells, &self.dependencies, &self.computation);
+    pub(crate) fn dependencies(&self) -> &[usize] {
+        &self.dependencies
+    }
+
+    pub(crate) fn value_type(&self) -> TypeId {
+        self.value_type
+    }
+
+    pub(crate) fn is_lazy(&self) -> bool {
+        self.lazy
     }
+
+    /// Whether this cell can simply be marked dirty when its dependencies change.
+    ///
+    /// Lazy cells with callbacks must still be recomputed: otherwise, we can't know
+    /// whether to call them.
+    pub(crate) fn deferrable(&self) -> bool {
+        self.lazy && self.callbacks.is_empty()
+    }
+
+    pub(crate) fn mark_dirty(&self) {
+        self.dirty.set(true);
+    }
+
+    /// Recompute the cached value, returning the previous value if it changed.
+    ///
+    /// A lazy cell which has never been computed has no previous value, so it's never
+    /// reported as changed; that's fine, because nobody can have observed it.
+    pub(crate) fn recompute(&self, cells: &[Option<Cell>]) -> Option<Box<dyn Value>> {
+        let value = Self::calculate(cells, &self.dependencies, &self.computation);
+        let previous = self.cache.replace(Some(value));
+        self.dirty.set(false);
+        previous.filter(|previous| !previous.eq_value(&*self.cache()))
+    }
+
+    /// Recompute the cached value if it's out of date.
+    pub(crate) fn refresh(&self, cells: &[Option<Cell>]) {
+        if self.dirty.get() {
+            self.recompute(cells);
+        }
+    }
+
+    /// The cached value, which is out of date if the cell is dirty.
+    pub(crate) fn cache(&self) -> Ref<'_, dyn Value> {
+        Ref::map(self.cache.borrow(), |cache| {
+            &**cache
+                .as_ref()
+                .expect("lazy cells are computed before they're read")
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RemoveCellError {
+    NonexistentCell,
+    /// The cell can't be removed while compute cells depend on it.
+    HasDependents,
 }
 
-pub(crate) enum Cell<T> {
-    Input(InputCell<T>),
-    Compute(ComputeCell<T>),
+pub(crate) enum Cell {
+    Input(InputCell),
+    Compute(ComputeCell),
 }
 
-impl<T: Copy + PartialEq> Cell<T> {
-    pub(crate) fn fwd(&self) -> &[ComputeCellID] {
+impl Cell {
+    pub(crate) fn fwd(&self) -> &[usize] {
         match self {
             Self::Input(input) => &input.fwd,
             Self::Compute(compute) => &compute.fwd,
         }
     }
 
-    pub(crate) fn fwd_mut(&mut self) -> &mut Vec<ComputeCellID> {
+    pub(crate) fn fwd_mut(&mut self) -> &mut Vec<usize> {
         match self {
             Self::Input(input) => &mut input.fwd,
             Self::Compute(compute) => &mut compute.fwd,
         }
     }
 
-    pub(crate) fn value(&self) -> T {
+    pub(crate) fn callbacks(&self) -> &[CallbackID] {
         match self {
-            Self::Input(ic) => ic.value,
-            Self::Compute(cc) => cc.cache,
+            Self::Input(input) => &input.callbacks,
+            Self::Compute(compute) => &compute.callbacks,
+        }
+    }
+
+    pub(crate) fn callbacks_mut(&mut self) -> &mut Vec<CallbackID> {
+        match self {
+            Self::Input(input) => &mut input.callbacks,
+            Self::Compute(compute) => &mut compute.callbacks,
+        }
+    }
+
+    /// The type of the cell's value.
+    pub(crate) fn value_type(&self) -> TypeId {
+        match self {
+            Self::Input(ic) => ic.value.borrow().as_any().type_id(),
+            Self::Compute(cc) => cc.value_type(),
+        }
+    }
+
+    /// The cell's value, which is out of date if it's a dirty compute cell.
+    fn value(&self) -> Ref<'_, dyn Value> {
+        match self {
+            Self::Input(ic) => Ref::map(ic.value.borrow(), |value| &**value),
+            Self::Compute(cc) => cc.cache(),
+        }
+    }
+
+    /// The up-to-date value of a cell, recomputing lazy compute cells as necessary.
+    ///
+    /// caution: use only when you know the cell exists
+    pub(crate) fn read(cells: &[Option<Cell>], idx: usize) -> Ref<'_, dyn Value> {
+        let cell = cells[idx]
+            .as_ref()
+            .expect("cells are never removed before their dependents");
+        if let Self::Compute(cc) = cell {
+            // dependencies always have lower IDs
+            cc.refresh(&cells[..idx]);
         }
+        cell.value()
     }
 }
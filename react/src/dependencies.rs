@@ -0,0 +1,159 @@
+use crate::{
+    error::CellError,
+    id::{AnyCellID, CellID, ComputeCellID, FallibleCellID, InputCellID},
+    value::Value,
+};
+use std::any::TypeId;
+
+/// An ID of a cell whose value is of a statically known type.
+pub trait TypedCellID: Copy {
+    /// The type of the cell's value, as returned by `Reactor::value`.
+    type Value: 'static + Clone + PartialEq;
+    /// The type passed to compute functions which depend on the cell.
+    type Input: 'static + Clone;
+
+    fn any(&self) -> AnyCellID;
+
+    /// Get the input to a compute function from the cell's value, which must be of
+    /// type `Value`.
+    ///
+    /// Returns an error if the cell is in error.
+    fn extract(value: &dyn Value) -> Result<Self::Input, CellError>;
+}
+
+/// An ID of a cell which can never be in error.
+///
+/// Only cells which can never be in error may be dependencies of an infallible compute cell.
+pub trait InfallibleCellID: TypedCellID {}
+
+fn downcast<T: 'static + Clone>(value: &dyn Value) -> T {
+    value
+        .as_any()
+        .downcast_ref::<T>()
+        .expect("dependency types are checked on creation")
+        .clone()
+}
+
+macro_rules! infallible_id {
+    ($name:ident) => {
+        impl<T: 'static + Clone + PartialEq> TypedCellID for $name<T> {
+            type Value = T;
+            type Input = T;
+
+            fn any(&self) -> AnyCellID {
+                (*self).into()
+            }
+
+            fn extract(value: &dyn Value) -> Result<T, CellError> {
+                Ok(downcast(value))
+            }
+        }
+
+        impl<T: 'static + Clone + PartialEq> InfallibleCellID for $name<T> {}
+    };
+}
+
+infallible_id!(InputCellID);
+infallible_id!(ComputeCellID);
+infallible_id!(CellID);
+
+impl<T: 'static + Clone + PartialEq> TypedCellID for FallibleCellID<T> {
+    type Value = Result<T, CellError>;
+    type Input = T;
+
+    fn any(&self) -> AnyCellID {
+        (*self).into()
+    }
+
+    fn extract(value: &dyn Value) -> Result<T, CellError> {
+        downcast(value)
+    }
+}
+
+/// The dependencies of a compute cell.
+///
+/// Tuples of IDs produce a tuple of values, so dependencies may be of different types.
+/// Slices of IDs produce a `Vec` of values.
+pub trait Dependencies {
+    /// The values of the dependencies, as passed to the compute function.
+    type Values: 'static;
+
+    /// The ID of each dependency, with the type of value it must hold.
+    fn ids(&self) -> Vec<(AnyCellID, TypeId)>;
+
+    /// Collect the values of the dependencies, in the same order as `ids`.
+    ///
+    /// Returns the first error among them, if any are in error.
+    fn values(values: &[&dyn Value]) -> Result<Self::Values, CellError>;
+}
+
+/// Dependencies which can never be in error.
+pub trait InfallibleDependencies: Dependencies {}
+
+macro_rules! tuple_dependencies {
+    ($($id:ident),+) => {
+        impl<$($id: TypedCellID),+> Dependencies for ($($id,)+) {
+            type Values = ($($id::Input,)+);
+
+            #[allow(non_snake_case)]
+            fn ids(&self) -> Vec<(AnyCellID, TypeId)> {
+                let ($($id,)+) = self;
+                vec![$(($id.any(), TypeId::of::<$id::Value>())),+]
+            }
+
+            fn values(values: &[&dyn Value]) -> Result<Self::Values, CellError> {
+                let mut values = values.iter();
+                Ok(($($id::extract(*values.next().expect("one value per dependency"))?,)+))
+            }
+        }
+
+        impl<$($id: InfallibleCellID),+> InfallibleDependencies for ($($id,)+) {}
+    };
+}
+
+tuple_dependencies!(A);
+tuple_dependencies!(A, B);
+tuple_dependencies!(A, B, C);
+tuple_dependencies!(A, B, C, D);
+tuple_dependencies!(A, B, C, D, E);
+tuple_dependencies!(A, B, C, D, E, F);
+tuple_dependencies!(A, B, C, D, E, F, G);
+tuple_dependencies!(A, B, C, D, E, F, G, H);
+
+fn slice_ids<I: TypedCellID>(ids: &[I]) -> Vec<(AnyCellID, TypeId)> {
+    ids.iter()
+        .map(|id| (id.any(), TypeId::of::<I::Value>()))
+        .collect()
+}
+
+fn slice_values<I: TypedCellID>(values: &[&dyn Value]) -> Result<Vec<I::Input>, CellError> {
+    values.iter().map(|value| I::extract(*value)).collect()
+}
+
+impl<I: TypedCellID> Dependencies for &[I] {
+    type Values = Vec<I::Input>;
+
+    fn ids(&self) -> Vec<(AnyCellID, TypeId)> {
+        slice_ids(self)
+    }
+
+    fn values(values: &[&dyn Value]) -> Result<Self::Values, CellError> {
+        slice_values::<I>(values)
+    }
+}
+
+impl<I: InfallibleCellID> InfallibleDependencies for &[I] {}
+
+impl<I: TypedCellID, const N: usize> Dependencies for &[I; N] {
+    type Values = Vec<I::Input>;
+
+    fn ids(&self) -> Vec<(AnyCellID, TypeId)> {
+        slice_ids(*self)
+    }
+
+    fn values(values: &[&dyn Value]) -> Result<Self::Values, CellError> {
+        slice_values::<I>(values)
+    }
+}
+
+impl<I: InfallibleCellID, const N: usize> InfallibleDependencies for &[I; N] {}
@@ -0,0 +1,55 @@
+use crate::id::AnyCellID;
+use std::{error::Error, fmt, sync::Arc};
+
+/// An error produced by a fallible compute cell.
+///
+/// Cells which depend on a cell in error are in error too, with the same `CellError`.
+#[derive(Clone, Debug)]
+pub struct CellError {
+    cell: AnyCellID,
+    error: Arc<dyn Error + Send + Sync>,
+}
+
+impl CellError {
+    pub(crate) fn new<E: 'static + Error + Send + Sync>(cell: AnyCellID, error: E) -> Self {
+        Self {
+            cell,
+            error: Arc::new(error),
+        }
+    }
+
+    /// The fallible compute cell whose computation failed.
+    pub fn cell(&self) -> AnyCellID {
+        self.cell
+    }
+
+    /// The error returned by the computation.
+    pub fn error(&self) -> &(dyn Error + Send + Sync + 'static) {
+        &*self.error
+    }
+
+    /// The error returned by the computation, if it's of type `E`.
+    pub fn downcast_ref<E: 'static + Error>(&self) -> Option<&E> {
+        self.error.downcast_ref()
+    }
+}
+
+// errors don't generally implement `PartialEq`, but we need to know whether a cell's
+// value has changed; errors from the same cell with the same message are the same.
+impl PartialEq for CellError {
+    fn eq(&self, other: &Self) -> bool {
+        self.cell == other.cell && self.error.to_string() == other.error.to_string()
+    }
+}
+
+impl fmt::Display for CellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cell {} failed: {}", self.cell.0, self.error)
+    }
+}
+
+impl Error for CellError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&*self.error)
+    }
+}
@@ -0,0 +1,82 @@
+use crate::{cell::Cell, id::AnyCellID, Reactor};
+use std::fmt::Write;
+
+/// The kind of a cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CellKind {
+    Input,
+    Compute,
+    LazyCompute,
+}
+
+impl CellKind {
+    fn name(self) -> &'static str {
+        match self {
+            CellKind::Input => "input",
+            CellKind::Compute => "compute",
+            CellKind::LazyCompute => "lazy compute",
+        }
+    }
+}
+
+impl<'a> Reactor<'a> {
+    // Lists every cell which exists, in order of creation, along with its kind.
+    pub fn cells(&self) -> impl '_ + Iterator<Item = (AnyCellID, CellKind)> {
+        self.cells.iter().enumerate().filter_map(|(idx, cell)| {
+            let kind = match cell.as_ref()? {
+                Cell::Input(_) => CellKind::Input,
+                Cell::Compute(cc) if cc.is_lazy() => CellKind::LazyCompute,
+                Cell::Compute(_) => CellKind::Compute,
+            };
+            Some((AnyCellID(idx), kind))
+        })
+    }
+
+    // Lists every dependency edge, as pairs of `(dependency, dependent)`.
+    //
+    // Each compute cell's dependencies are listed in the order in which its compute function
+    // takes them, so a cell which depends on another more than once has several edges to it.
+    pub fn edges(&self) -> impl '_ + Iterator<Item = (AnyCellID, AnyCellID)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, cell)| match cell {
+                Some(Cell::Compute(cc)) => Some((idx, cc.dependencies())),
+                _ => None,
+            })
+            .flat_map(|(idx, dependencies)| {
+                dependencies
+                    .iter()
+                    .map(move |&dependency| (AnyCellID(dependency), AnyCellID(idx)))
+            })
+    }
+
+    // Describes the dependency graph in the Graphviz DOT language.
+    //
+    // Each cell is labelled with its kind and ID; edges point from dependencies to
+    // their dependents.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph reactor {\n");
+        for (AnyCellID(idx), kind) in self.cells() {
+            let shape = match kind {
+                CellKind::Input => "box",
+                CellKind::Compute => "ellipse",
+                CellKind::LazyCompute => "ellipse, style=dashed",
+            };
+            // writing to a `String` can't fail
+            let _ = writeln!(
+                dot,
+                "    cell{} [label=\"{} {}\", shape={}];",
+                idx,
+                kind.name(),
+                idx,
+                shape
+            );
+        }
+        for (AnyCellID(from), AnyCellID(to)) in self.edges() {
+            let _ = writeln!(dot, "    cell{} -> cell{};", from, to);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
@@ -1,48 +1,230 @@
-/// `InputCellID` is a unique identifier for an input cell.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
-pub struct InputCellID(pub(crate) usize);
-/// `ComputeCellID` is a unique identifier for a compute cell.
-/// Values of type `InputCellID` and `ComputeCellID` should not be mutually assignable,
-/// demonstrated by the following tests:
-///
-/// ```compile_fail
-/// let mut r = react::Reactor::new();
-/// let input: react::ComputeCellID = r.create_input(111);
-/// ```
-///
-/// ```compile_fail
-/// let mut r = react::Reactor::new();
-/// let input = r.create_input(111);
-/// let compute: react::InputCellID = r.create_compute(&[react::CellID::Input(input)], |_| 222).unwrap();
-/// ```
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
-pub struct ComputeCellID(pub(crate) usize);
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+use crate::error::CellError;
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+// Typed IDs only carry their value type as a marker, so they are `Copy`, `Eq`, etc.
+// whatever that type is. Deriving those traits would require the same of the value type,
+// so this macro implements them by hand.
+macro_rules! typed_id {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        pub struct $name<T>(pub(crate) usize, PhantomData<fn() -> T>);
+
+        impl<T> $name<T> {
+            pub(crate) fn new(idx: usize) -> Self {
+                Self(idx, PhantomData)
+            }
+        }
+
+        impl<T> Clone for $name<T> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<T> Copy for $name<T> {}
+
+        impl<T> fmt::Debug for $name<T> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_tuple(stringify!($name)).field(&self.0).finish()
+            }
+        }
+
+        impl<T> PartialEq for $name<T> {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+
+        impl<T> Eq for $name<T> {}
+
+        impl<T> PartialOrd for $name<T> {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl<T> Ord for $name<T> {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.0.cmp(&other.0)
+            }
+        }
+
+        impl<T> Hash for $name<T> {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.0.hash(state)
+            }
+        }
+    };
+}
+
+typed_id!(
+    /// `InputCellID` is a unique identifier for an input cell holding a `T`.
+    InputCellID
+);
+
+typed_id!(
+    /// `ComputeCellID` is a unique identifier for a compute cell holding a `T`.
+    /// Values of type `InputCellID` and `ComputeCellID` should not be mutually assignable,
+    /// demonstrated by the following tests:
+    ///
+    /// ```compile_fail
+    /// let mut r = react::Reactor::new();
+    /// let input: react::ComputeCellID<i32> = r.create_input(111);
+    /// ```
+    ///
+    /// ```compile_fail
+    /// let mut r = react::Reactor::new();
+    /// let input = r.create_input(111);
+    /// let compute: react::InputCellID<i32> = r.create_compute(&[react::CellID::Input(input)], |_| 222).unwrap();
+    /// ```
+    ///
+    /// Nor should IDs of cells holding different types:
+    ///
+    /// ```compile_fail
+    /// let mut r = react::Reactor::new();
+    /// let input = r.create_input(111);
+    /// let compute: react::ComputeCellID<bool> = r.create_compute(&[input], |v| v[0] + 1).unwrap();
+    /// ```
+    ComputeCellID
+);
+
+typed_id!(
+    /// `FallibleCellID` is a unique identifier for a fallible compute cell, whose value is
+    /// either a `T` or an error.
+    ///
+    /// As a dependency, it provides a `T`; a cell which depends on it while it's in error is
+    /// in error too. Only fallible compute cells may depend on it:
+    ///
+    /// ```compile_fail
+    /// # #[derive(Debug)] struct E;
+    /// # impl std::fmt::Display for E {
+    /// #     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { Ok(()) }
+    /// # }
+    /// # impl std::error::Error for E {}
+    /// let mut r = react::Reactor::new();
+    /// let input = r.create_input(111);
+    /// let fallible = r.create_fallible_compute((input,), |(v,)| Ok::<_, E>(v)).unwrap();
+    /// let compute = r.create_compute((fallible,), |(v,)| v + 1).unwrap();
+    /// ```
+    FallibleCellID
+);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct CallbackID(pub(crate) usize);
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
-pub enum CellID {
-    Input(InputCellID),
-    Compute(ComputeCellID),
+/// `CellID` identifies either kind of cell holding a `T`.
+pub enum CellID<T> {
+    Input(InputCellID<T>),
+    Compute(ComputeCellID<T>),
 }
 
-impl CellID {
+impl<T> CellID<T> {
     pub(crate) fn idx(&self) -> usize {
         match self {
-            Self::Input(InputCellID(idx)) => *idx,
-            Self::Compute(ComputeCellID(idx)) => *idx,
+            Self::Input(InputCellID(idx, _)) => *idx,
+            Self::Compute(ComputeCellID(idx, _)) => *idx,
         }
     }
+
+    // inputs sort before compute cells
+    fn key(&self) -> (bool, usize) {
+        (matches!(self, Self::Compute(_)), self.idx())
+    }
+}
+
+impl<T> Clone for CellID<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
 }
 
-impl From<InputCellID> for CellID {
-    fn from(id: InputCellID) -> CellID {
+impl<T> Copy for CellID<T> {}
+
+impl<T> fmt::Debug for CellID<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Input(id) => f.debug_tuple("Input").field(id).finish(),
+            Self::Compute(id) => f.debug_tuple("Compute").field(id).finish(),
+        }
+    }
+}
+
+impl<T> PartialEq for CellID<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl<T> Eq for CellID<T> {}
+
+impl<T> PartialOrd for CellID<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for CellID<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl<T> Hash for CellID<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
+    }
+}
+
+impl<T> From<InputCellID<T>> for CellID<T> {
+    fn from(id: InputCellID<T>) -> CellID<T> {
         Self::Input(id)
     }
 }
 
-impl From<ComputeCellID> for CellID {
-    fn from(id: ComputeCellID) -> CellID {
+impl<T> From<ComputeCellID<T>> for CellID<T> {
+    fn from(id: ComputeCellID<T>) -> CellID<T> {
         Self::Compute(id)
     }
 }
+
+/// `AnyCellID` identifies a cell without regard to the type of its value.
+///
+/// It's returned when the type of a cell can't be known statically, such as when
+/// reporting which of a compute cell's dependencies doesn't exist.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AnyCellID(pub(crate) usize);
+
+impl<T> From<InputCellID<T>> for AnyCellID {
+    fn from(id: InputCellID<T>) -> AnyCellID {
+        AnyCellID(id.0)
+    }
+}
+
+impl<T> From<ComputeCellID<T>> for AnyCellID {
+    fn from(id: ComputeCellID<T>) -> AnyCellID {
+        AnyCellID(id.0)
+    }
+}
+
+impl<T> From<FallibleCellID<T>> for AnyCellID {
+    fn from(id: FallibleCellID<T>) -> AnyCellID {
+        AnyCellID(id.0)
+    }
+}
+
+impl<T> From<FallibleCellID<T>> for ComputeCellID<Result<T, CellError>> {
+    fn from(id: FallibleCellID<T>) -> Self {
+        ComputeCellID::new(id.0)
+    }
+}
+
+impl<T> From<CellID<T>> for AnyCellID {
+    fn from(id: CellID<T>) -> AnyCellID {
+        AnyCellID(id.idx())
+    }
+}
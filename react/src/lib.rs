@@ -1,179 +1,502 @@
 pub mod id;
-pub use id::{CallbackID, CellID, ComputeCellID, InputCellID};
+pub use id::{AnyCellID, CallbackID, CellID, ComputeCellID, FallibleCellID, InputCellID};
 
 pub mod cell;
+pub use cell::RemoveCellError;
 use cell::{Cell, ComputeCell, InputCell};
 
 pub mod callback;
 use callback::Callback;
 pub use callback::RemoveCallbackError;
 
+pub mod batch;
+pub use batch::Batch;
+
+pub mod graph;
+pub use graph::CellKind;
+
+pub mod trace;
+pub use trace::Trace;
+use trace::Tracer;
+
+pub mod dependencies;
+pub use dependencies::{Dependencies, InfallibleCellID, InfallibleDependencies, TypedCellID};
+
+pub mod error;
+pub use error::CellError;
+
+pub mod sync;
+pub use sync::SyncReactor;
+
+mod value;
+use value::Value;
+
+use std::{
+    any::TypeId,
+    sync::mpsc::{self, Receiver},
+};
+
+/// A reactive system of cells.
+///
+/// Cells may hold values of any `'static + Clone + PartialEq` type, and a single
+/// reactor may contain cells of many different types.
 #[derive(Default)]
-pub struct Reactor<'a, T> {
-    cells: Vec<Cell<T>>,
-    callbacks: Vec<Option<Callback<'a, T>>>,
+pub struct Reactor<'a> {
+    /// Removed cells leave a tombstone behind, so that IDs are never reused.
+    cells: Vec<Option<Cell>>,
+    callbacks: Vec<Option<Callback<'a>>>,
+    tracer: Option<Tracer<'a>>,
 }
 
-impl<'a, T: Copy + PartialEq + Default> Reactor<'a, T> {
+impl<'a> Reactor<'a> {
     pub fn new() -> Self {
         Default::default()
     }
 
-    fn push_cell(&mut self, cell: Cell<T>) -> usize {
+    fn cell(&self, idx: usize) -> Option<&Cell> {
+        self.cells.get(idx)?.as_ref()
+    }
+
+    fn cell_mut(&mut self, idx: usize) -> Option<&mut Cell> {
+        self.cells.get_mut(idx)?.as_mut()
+    }
+
+    /// Get a cell which is known to exist, such as one referred to by a `fwd` list.
+    fn live(&self, idx: usize) -> &Cell {
+        self.cells[idx]
+            .as_ref()
+            .expect("cells are only referred to while they exist")
+    }
+
+    /// Get a cell which is known to exist, such as one referred to by a `fwd` list.
+    fn live_mut(&mut self, idx: usize) -> &mut Cell {
+        self.cells[idx]
+            .as_mut()
+            .expect("cells are only referred to while they exist")
+    }
+
+    fn push_cell(&mut self, cell: Cell) -> usize {
         let idx = self.cells.len();
-        self.cells.push(cell);
+        self.cells.push(Some(cell));
         idx
     }
 
     // Creates an input cell with the specified initial value, returning its ID.
-    pub fn create_input(&mut self, initial: T) -> InputCellID {
-        InputCellID(self.push_cell(Cell::Input(InputCell::new(initial))))
+    pub fn create_input<T>(&mut self, initial: T) -> InputCellID<T>
+    where
+        T: 'static + Clone + PartialEq,
+    {
+        InputCellID::new(self.push_cell(Cell::Input(InputCell::new(Box::new(initial)))))
     }
 
     // Creates a compute cell with the specified dependencies and compute function.
     //
     // The compute function is expected to take in its arguments in the same order as specified in
-    // `dependencies`.
+    // `dependencies`. A tuple of dependencies, which may each hold a different type, produces a
+    // tuple of values; an array or slice of dependencies produces a `Vec` of values.
     //
     // If any dependency doesn't exist, returns an Err with that nonexistent dependency.
-    pub fn create_compute<F>(
+    pub fn create_compute<D, F, U>(
         &mut self,
-        dependencies: &[CellID],
+        dependencies: D,
         computation: F,
-    ) -> Result<ComputeCellID, CellID>
+    ) -> Result<ComputeCellID<U>, AnyCellID>
     where
-        F: 'static + Fn(&[T]) -> T,
+        D: InfallibleDependencies,
+        F: 'static + Fn(D::Values) -> U,
+        U: 'static + Clone + PartialEq,
     {
-        let idx = ComputeCellID(self.push_cell(Cell::Compute(ComputeCell::new(
-            self,
+        self.push_compute(
             dependencies,
-            computation,
-        )?)));
-        for dependency in dependencies.iter() {
-            if dependency.idx() >= self.cells.len() {
-                return Err(*dependency);
-            }
-            self.cells[dependency.idx()].fwd_mut().push(idx.into());
+            move |values| {
+                computation(
+                    values.unwrap_or_else(|_| {
+                        unreachable!("infallible dependencies are never in error")
+                    }),
+                )
+            },
+            false,
+        )
+    }
+
+    // Creates a lazy compute cell with the specified dependencies and compute function.
+    //
+    // This behaves just like a compute cell created by `create_compute`, except that it's
+    // not computed when its dependencies change, only when its value is next needed: when
+    // it's read by `value`, or by the computation of another cell which is not lazy.
+    //
+    // Lazy cells with callbacks must be computed whenever their dependencies change, to
+    // determine whether to call the callbacks.
+    pub fn create_lazy_compute<D, F, U>(
+        &mut self,
+        dependencies: D,
+        computation: F,
+    ) -> Result<ComputeCellID<U>, AnyCellID>
+    where
+        D: InfallibleDependencies,
+        F: 'static + Fn(D::Values) -> U,
+        U: 'static + Clone + PartialEq,
+    {
+        self.push_compute(
+            dependencies,
+            move |values| {
+                computation(
+                    values.unwrap_or_else(|_| {
+                        unreachable!("infallible dependencies are never in error")
+                    }),
+                )
+            },
+            true,
+        )
+    }
+
+    // Creates a fallible compute cell with the specified dependencies and compute function.
+    //
+    // The compute function returns a `Result`. If it returns an Err, the cell is in error:
+    // `value` returns that error, as a `CellError`, and every fallible compute cell which
+    // depends on this one is in error too, without its compute function being called.
+    //
+    // Fallible compute cells may depend on other fallible compute cells, but infallible
+    // ones may not.
+    //
+    // If any dependency doesn't exist, returns an Err with that nonexistent dependency.
+    pub fn create_fallible_compute<D, F, U, E>(
+        &mut self,
+        dependencies: D,
+        computation: F,
+    ) -> Result<FallibleCellID<U>, AnyCellID>
+    where
+        D: Dependencies,
+        F: 'static + Fn(D::Values) -> Result<U, E>,
+        U: 'static + Clone + PartialEq,
+        E: 'static + std::error::Error + Send + Sync,
+    {
+        let origin = AnyCellID(self.cells.len());
+        let id: ComputeCellID<Result<U, CellError>> = self.push_compute(
+            dependencies,
+            move |values| {
+                values.and_then(|values| {
+                    computation(values).map_err(|error| CellError::new(origin, error))
+                })
+            },
+            false,
+        )?;
+        Ok(FallibleCellID::new(id.0))
+    }
+
+    /// Create a compute cell whose computation is given the values of its dependencies,
+    /// or the first error among them.
+    fn push_compute<D, F, U>(
+        &mut self,
+        dependencies: D,
+        computation: F,
+        lazy: bool,
+    ) -> Result<ComputeCellID<U>, AnyCellID>
+    where
+        D: Dependencies,
+        F: 'static + Fn(Result<D::Values, CellError>) -> U,
+        U: 'static + Clone + PartialEq,
+    {
+        let cell = ComputeCell::new(&self.cells, dependencies, computation, lazy)?;
+        let dependencies = cell.dependencies().to_owned();
+        let idx = self.push_cell(Cell::Compute(cell));
+        for dependency in dependencies {
+            self.live_mut(dependency).fwd_mut().push(idx);
         }
-        Ok(idx)
+        Ok(ComputeCellID::new(idx))
     }
 
-    fn idx(&self, id: CellID) -> Option<usize> {
-        if id.idx() >= self.cells.len() {
-            None
-        } else {
-            Some(id.idx())
+    // Removes the specified cell, along with its callbacks.
+    //
+    // Returns an Err if the cell does not exist, or if any compute cell depends on it.
+    //
+    // The IDs of removed cells are never reused, so any remaining copies of the ID simply
+    // refer to no cell at all.
+    pub fn remove_cell<I: Into<AnyCellID>>(&mut self, id: I) -> Result<(), RemoveCellError> {
+        let idx = id.into().0;
+        let cell = self.cell(idx).ok_or(RemoveCellError::NonexistentCell)?;
+        if !cell.fwd().is_empty() {
+            return Err(RemoveCellError::HasDependents);
+        }
+        self.remove(idx);
+        Ok(())
+    }
+
+    // Removes the specified cell, along with every compute cell which depends on it,
+    // directly or indirectly.
+    //
+    // Returns the IDs of all the removed cells, or an Err if the cell does not exist.
+    pub fn remove_cell_cascading<I: Into<AnyCellID>>(
+        &mut self,
+        id: I,
+    ) -> Result<Vec<AnyCellID>, RemoveCellError> {
+        let idx = id.into().0;
+        self.cell(idx).ok_or(RemoveCellError::NonexistentCell)?;
+
+        let mut remove = vec![idx];
+        let mut idx = 0;
+        while idx < remove.len() {
+            remove.extend(self.live(remove[idx]).fwd());
+            idx += 1;
+        }
+        remove.sort_unstable();
+        remove.dedup();
+
+        // dependents always have higher IDs than their dependencies, so removing the
+        // highest first means no removed cell is ever referred to by a remaining one
+        for &idx in remove.iter().rev() {
+            self.remove(idx);
+        }
+        Ok(remove.into_iter().map(AnyCellID).collect())
+    }
+
+    /// Remove a cell which no other cell depends on, unlinking it from its dependencies.
+    fn remove(&mut self, idx: usize) {
+        let cell = self.cells[idx]
+            .take()
+            .expect("only existing cells are removed");
+        for &CallbackID(cbid) in cell.callbacks() {
+            self.callbacks[cbid] = None;
+        }
+        if let Cell::Compute(cc) = cell {
+            for &dependency in cc.dependencies() {
+                self.live_mut(dependency)
+                    .fwd_mut()
+                    .retain(|&dependent| dependent != idx);
+            }
         }
     }
 
     // Retrieves the current value of the cell, or None if the cell does not exist.
-    pub fn value(&self, id: CellID) -> Option<T> {
-        Some(self.cells[self.idx(id)?].value())
+    pub fn value<I: TypedCellID>(&self, id: I) -> Option<I::Value> {
+        let idx = id.any().0;
+        self.cell(idx)?;
+        Cell::read(&self.cells, idx)
+            .as_any()
+            .downcast_ref::<I::Value>()
+            .cloned()
     }
 
     // Sets the value of the specified input cell.
-    pub fn set_value(&mut self, id: InputCellID, new_value: T) -> bool {
-        let idx = match self.idx(id.into()) {
-            None => return false,
-            Some(idx) => idx,
-        };
+    pub fn set_value<T: 'static + PartialEq>(&mut self, id: InputCellID<T>, new_value: T) -> bool {
+        match self.assign(id, new_value) {
+            None => false,
+            Some(previous) => {
+                self.propagate(vec![(id.0, Box::new(previous))]);
+                true
+            }
+        }
+    }
 
-        let mut recompute;
-        match self.cells[idx] {
-            Cell::Compute(_) => return false,
-            Cell::Input(ref mut ic) => {
-                ic.value = new_value;
-
-                // Construct a list of cells to recompute.
-                // Rules:
-                // - we can visit each cell exactly once
-                // - all back-refs must be satisfied before visiting a cell
-                //
-                // This would be a fairly complex topo-sorting operation, but
-                // we have a massive advantage: we know that all cell IDs can
-                // only refer to lower cell IDs numerically. That makes things
-                // simple: just traverse recursively, then sort, then dedup.
-                recompute = ic.fwd.to_owned();
-                // we can't use normal loop operations, because we have to
-                // repeatedly extend the recompute list during iteration.
-                let mut idx = 0;
-                while idx < recompute.len() {
-                    let ComputeCellID(id) = recompute[idx];
-                    recompute.extend(self.cells[id].fwd());
-                    idx += 1;
-                }
+    // Applies several updates at once.
+    //
+    // The updates made within `updates` are propagated in a single pass once it returns,
+    // so each compute cell is recomputed at most once, and its callbacks are called at most
+    // once, with its final value. Compute cells whose final value is the same as before
+    // the batch don't call their callbacks at all.
+    pub fn batch<F, R>(&mut self, updates: F) -> R
+    where
+        F: FnOnce(&mut Batch<'_, 'a>) -> R,
+    {
+        let mut batch = Batch::new(self);
+        let result = updates(&mut batch);
+        let changed = batch.into_changed();
+        self.propagate(changed);
+        result
+    }
+
+    /// Set the value of an input cell without propagating it, returning its previous value.
+    ///
+    /// Returns None if the cell doesn't exist.
+    pub(crate) fn assign<T: 'static>(&mut self, id: InputCellID<T>, new_value: T) -> Option<T> {
+        match self.cell_mut(id.0) {
+            Some(Cell::Input(ic)) => ic
+                .value
+                .get_mut()
+                .as_any_mut()
+                .downcast_mut::<T>()
+                .map(|value| std::mem::replace(value, new_value)),
+            _ => None,
+        }
+    }
 
-                recompute.sort();
-                recompute.dedup();
+    /// Call each of a cell's callbacks with its previous and new values.
+    fn call(
+        callbacks: &mut [Option<Callback<'a>>],
+        ids: &[CallbackID],
+        previous: &dyn Value,
+        value: &dyn Value,
+    ) {
+        for &CallbackID(cbid) in ids {
+            match callbacks[cbid] {
+                None => unreachable!("only legal callbacks are retained"),
+                Some(ref mut cb) => cb(previous, value),
             }
         }
+    }
 
-        for redo_id in recompute {
-            let ComputeCellID(idx) = redo_id;
+    /// Recompute every cell depending on the given input cells, and call the callbacks of
+    /// those whose value changed.
+    ///
+    /// Each input cell is given with its value from before the update.
+    fn propagate(&mut self, inputs: Vec<(usize, Box<dyn Value>)>) {
+        // Construct a list of cells to recompute.
+        // Rules:
+        // - we can visit each cell exactly once
+        // - all back-refs must be satisfied before visiting a cell
+        //
+        // This would be a fairly complex topo-sorting operation, but
+        // we have a massive advantage: we know that all cell IDs can
+        // only refer to lower cell IDs numerically, because IDs are never
+        // reused, even after removing cells. That makes things simple:
+        // just traverse recursively, then sort, then dedup.
+        let mut recompute: Vec<usize> = inputs
+            .iter()
+            .flat_map(|(idx, _)| self.live(*idx).fwd())
+            .copied()
+            .collect();
+        // we can't use normal loop operations, because we have to
+        // repeatedly extend the recompute list during iteration.
+        let mut idx = 0;
+        while idx < recompute.len() {
+            recompute.extend(self.live(recompute[idx]).fwd());
+            idx += 1;
+        }
 
-            // split the range so we can immutably borrow the lower portion while
-            // we mutably borrow the upper portion
-            let (lower, upper) = self.cells.split_at_mut(idx);
+        recompute.sort_unstable();
+        recompute.dedup();
+
+        // only bother recording what happens if anybody is listening
+        let mut trace = self.tracer.as_ref().map(|_| Trace {
+            inputs: inputs.iter().map(|(idx, _)| AnyCellID(*idx)).collect(),
+            ..Trace::default()
+        });
+
+        for (idx, previous) in &inputs {
+            let ic = match self.cells[*idx] {
+                Some(Cell::Input(ref ic)) => ic,
+                _ => unreachable!("only input cells are set"),
+            };
+            let value = ic.value.borrow();
+            if !value.eq_value(&**previous) {
+                if let Some(trace) = trace.as_mut() {
+                    trace.callbacks.extend(&ic.callbacks);
+                }
+                Self::call(&mut self.callbacks, &ic.callbacks, &**previous, &**value);
+            }
+        }
+
+        for idx in recompute {
+            // dependencies always have lower IDs
+            let (lower, upper) = self.cells.split_at(idx);
             match upper[0] {
-                Cell::Compute(ref mut cc) => {
-                    let value = cc.cache;
-                    cc.recompute(lower);
-                    if value != cc.cache {
-                        for &CallbackID(cbid) in &cc.callbacks {
-                            match self.callbacks[cbid] {
-                                None => unreachable!("only legal callbacks are retained"),
-                                Some(ref mut cb) => cb(cc.cache),
-                            }
+                Some(Cell::Compute(ref cc)) if cc.deferrable() => {
+                    cc.mark_dirty();
+                    if let Some(trace) = trace.as_mut() {
+                        trace.deferred.push(AnyCellID(idx));
+                    }
+                }
+                Some(Cell::Compute(ref cc)) => {
+                    let previous = cc.recompute(lower);
+                    if let Some(trace) = trace.as_mut() {
+                        trace.recomputed.push(AnyCellID(idx));
+                        if previous.is_some() {
+                            trace.changed.push(AnyCellID(idx));
+                            trace.callbacks.extend(&cc.callbacks);
                         }
                     }
+                    if let Some(previous) = previous {
+                        Self::call(&mut self.callbacks, &cc.callbacks, &*previous, &*cc.cache());
+                    }
                 }
                 _ => unreachable!(),
             }
         }
 
-        true
-    }
-
-    fn compute_mut(&mut self, id: ComputeCellID) -> Option<&mut ComputeCell<T>> {
-        let ComputeCellID(idx) = id;
-        if idx >= self.cells.len() {
-            return None;
+        if let (Some(tracer), Some(trace)) = (self.tracer.as_mut(), trace) {
+            tracer(&trace);
         }
+    }
 
-        match self.cells[idx] {
-            Cell::Input(_) => return None,
-            Cell::Compute(ref mut cc) => Some(cc),
+    fn typed_cell_mut<I: TypedCellID>(&mut self, id: I) -> Option<&mut Cell> {
+        let cell = self.cell_mut(id.any().0)?;
+        if cell.value_type() == TypeId::of::<I::Value>() {
+            Some(cell)
+        } else {
+            None
         }
     }
 
-    // Adds a callback to the specified compute cell.
+    // Adds a callback to the specified cell.
     //
     // Returns the ID of the just-added callback, or None if the cell doesn't exist.
     //
-    // Callbacks on input cells will not be tested.
-    //
     // The semantics of callbacks:
-    // For a single set_value call, each compute cell's callbacks should each be called:
-    // * Zero times if the compute cell's value did not change as a result of the set_value call.
-    // * Exactly once if the compute cell's value changed as a result of the set_value call.
-    //   The value passed to the callback is the final value of the compute cell after the
+    // For a single set_value call, each cell's callbacks should each be called:
+    // * Zero times if the cell's value did not change as a result of the set_value call.
+    // * Exactly once if the cell's value changed as a result of the set_value call.
+    //   The value passed to the callback is the final value of the cell after the
     //   set_value call.
-    pub fn add_callback<F>(&mut self, id: ComputeCellID, callback: F) -> Option<CallbackID>
+    pub fn add_callback<I, F>(&mut self, id: I, mut callback: F) -> Option<CallbackID>
+    where
+        I: TypedCellID,
+        F: 'a + FnMut(I::Value),
+    {
+        self.add_change_callback(id, move |_, value| callback(value))
+    }
+
+    // Adds a callback to the specified cell, which is called with both the previous value
+    // of the cell and its new one.
+    //
+    // Otherwise, this behaves just like a callback added by add_callback.
+    pub fn add_change_callback<I, F>(&mut self, id: I, mut callback: F) -> Option<CallbackID>
     where
-        F: 'a + FnMut(T),
+        I: TypedCellID,
+        F: 'a + FnMut(I::Value, I::Value),
     {
+        self.typed_cell_mut(id)?;
+        // a lazy cell may be out of date; if so, bring it up to date now, so that its
+        // callbacks aren't called for changes which happened before they were added
+        Cell::read(&self.cells, id.any().0);
+
         let cb_idx = self.callbacks.len();
-        self.compute_mut(id)?.callbacks.push(CallbackID(cb_idx));
-        self.callbacks.push(Some(Box::new(callback)));
+        self.typed_cell_mut(id)?
+            .callbacks_mut()
+            .push(CallbackID(cb_idx));
+        let downcast = |value: &dyn Value| {
+            value
+                .as_any()
+                .downcast_ref::<I::Value>()
+                .expect("callbacks are only added to cells of their type")
+                .clone()
+        };
+        self.callbacks.push(Some(Box::new(move |previous, value| {
+            callback(downcast(previous), downcast(value))
+        })));
         Some(CallbackID(cb_idx))
     }
 
+    // Subscribes to the specified cell through a channel, as an alternative to a
+    // callback: each value which would be passed to a callback is sent on the channel instead.
+    //
+    // Returns the ID of the underlying callback, with which the subscription can be removed
+    // by remove_callback, along with the receiving end of the channel; or None if the cell
+    // doesn't exist.
+    pub fn subscribe<I: TypedCellID>(&mut self, id: I) -> Option<(CallbackID, Receiver<I::Value>)> {
+        let (sender, receiver) = mpsc::channel();
+        let callback = self.add_callback(id, move |value| {
+            // if the receiver has hung up, nobody is interested in the value any more
+            let _ = sender.send(value);
+        })?;
+        Some((callback, receiver))
+    }
+
     // Removes the specified callback, using an ID returned from add_callback.
     //
     // Returns an Err if either the cell or callback does not exist.
-    pub fn remove_callback(
+    pub fn remove_callback<I: TypedCellID>(
         &mut self,
-        cell: ComputeCellID,
+        cell: I,
         callback: CallbackID,
     ) -> Result<(), RemoveCallbackError> {
         let CallbackID(idx) = callback;
@@ -183,9 +506,9 @@ impl<'a, T: Copy + PartialEq + Default> Reactor<'a, T> {
         if self.callbacks[idx].is_none() {
             return Err(RemoveCallbackError::NonexistentCallback);
         }
-        self.compute_mut(cell)
+        self.typed_cell_mut(cell)
             .ok_or(RemoveCallbackError::NonexistentCell)?
-            .callbacks
+            .callbacks_mut()
             .retain(|cbid| *cbid != callback);
         self.callbacks[idx] = None;
 
@@ -0,0 +1,250 @@
+use crate::{
+    AnyCellID, Batch, CallbackID, CellKind, ComputeCellID, Dependencies, FallibleCellID,
+    InfallibleDependencies, InputCellID, Reactor, RemoveCallbackError, RemoveCellError, Trace,
+    TypedCellID,
+};
+use std::ops::{Deref, DerefMut};
+use std::sync::{mpsc::Receiver, Mutex, MutexGuard};
+use std::thread::{self, ThreadId};
+
+/// A `Reactor` which only ever holds `Send` values, computations, and callbacks.
+#[derive(Default)]
+struct SendReactor(Reactor<'static>);
+
+// SAFETY: a `Reactor` is only `!Send` because it holds type-erased values, computations,
+// callbacks, and a tracer. `SyncReactor` only ever adds those which are `Send`, and there is no other
+// way to reach the inner reactor. `Batch` can only replace input values with values of
+// the same type, so it can't introduce anything `!Send` either.
+unsafe impl Send for SendReactor {}
+
+/// A reactive system of cells which can be shared between threads.
+///
+/// This offers the same operations as `Reactor`, but through a shared reference, and
+/// requires that all values, computations, and callbacks be `Send`. Updates from
+/// different threads are serialized: each is applied and fully propagated, calling any
+/// callbacks, before the next begins.
+///
+/// # Re-entry
+///
+/// Computations, callbacks, and the tracer run while the reactor is locked, so they
+/// must not use the `SyncReactor` which runs them. Doing so panics rather than
+/// deadlocking. To react to a change by updating the reactor, `subscribe` to the cell
+/// and make the update from the thread receiving the values.
+#[derive(Default)]
+pub struct SyncReactor {
+    inner: Mutex<SendReactor>,
+    /// The thread currently holding `inner`, if any
+    owner: Mutex<Option<ThreadId>>,
+}
+
+/// Access to the reactor, recording which thread holds it
+struct Guard<'a> {
+    reactor: MutexGuard<'a, SendReactor>,
+    owner: &'a Mutex<Option<ThreadId>>,
+}
+
+impl Deref for Guard<'_> {
+    type Target = SendReactor;
+    fn deref(&self) -> &SendReactor {
+        &self.reactor
+    }
+}
+
+impl DerefMut for Guard<'_> {
+    fn deref_mut(&mut self) -> &mut SendReactor {
+        &mut self.reactor
+    }
+}
+
+impl Drop for Guard<'_> {
+    fn drop(&mut self) {
+        *lock_owner(self.owner) = None;
+    }
+}
+
+// the owner is only ever replaced wholesale, so it's valid even if a panic poisoned it
+fn lock_owner(owner: &Mutex<Option<ThreadId>>) -> MutexGuard<'_, Option<ThreadId>> {
+    owner.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+impl SyncReactor {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn lock(&self) -> Guard<'_> {
+        let current = thread::current().id();
+        if *lock_owner(&self.owner) == Some(current) {
+            panic!("a computation, callback, or tracer used the SyncReactor running it");
+        }
+        let reactor = self
+            .inner
+            .lock()
+            .expect("a computation or callback panicked during an earlier update");
+        *lock_owner(&self.owner) = Some(current);
+        Guard {
+            reactor,
+            owner: &self.owner,
+        }
+    }
+
+    /// See `Reactor::create_input`.
+    pub fn create_input<T>(&self, initial: T) -> InputCellID<T>
+    where
+        T: 'static + Clone + PartialEq + Send,
+    {
+        self.lock().0.create_input(initial)
+    }
+
+    /// See `Reactor::create_compute`.
+    pub fn create_compute<D, F, U>(
+        &self,
+        dependencies: D,
+        computation: F,
+    ) -> Result<ComputeCellID<U>, AnyCellID>
+    where
+        D: InfallibleDependencies,
+        F: 'static + Fn(D::Values) -> U + Send,
+        U: 'static + Clone + PartialEq + Send,
+    {
+        self.lock().0.create_compute(dependencies, computation)
+    }
+
+    /// See `Reactor::create_lazy_compute`.
+    pub fn create_lazy_compute<D, F, U>(
+        &self,
+        dependencies: D,
+        computation: F,
+    ) -> Result<ComputeCellID<U>, AnyCellID>
+    where
+        D: InfallibleDependencies,
+        F: 'static + Fn(D::Values) -> U + Send,
+        U: 'static + Clone + PartialEq + Send,
+    {
+        self.lock().0.create_lazy_compute(dependencies, computation)
+    }
+
+    /// See `Reactor::create_fallible_compute`.
+    pub fn create_fallible_compute<D, F, U, E>(
+        &self,
+        dependencies: D,
+        computation: F,
+    ) -> Result<FallibleCellID<U>, AnyCellID>
+    where
+        D: Dependencies,
+        F: 'static + Fn(D::Values) -> Result<U, E> + Send,
+        U: 'static + Clone + PartialEq + Send,
+        E: 'static + std::error::Error + Send + Sync,
+    {
+        self.lock()
+            .0
+            .create_fallible_compute(dependencies, computation)
+    }
+
+    /// See `Reactor::remove_cell`.
+    pub fn remove_cell<I: Into<AnyCellID>>(&self, id: I) -> Result<(), RemoveCellError> {
+        self.lock().0.remove_cell(id)
+    }
+
+    /// See `Reactor::remove_cell_cascading`.
+    pub fn remove_cell_cascading<I: Into<AnyCellID>>(
+        &self,
+        id: I,
+    ) -> Result<Vec<AnyCellID>, RemoveCellError> {
+        self.lock().0.remove_cell_cascading(id)
+    }
+
+    /// See `Reactor::value`.
+    pub fn value<I: TypedCellID>(&self, id: I) -> Option<I::Value> {
+        self.lock().0.value(id)
+    }
+
+    /// See `Reactor::set_value`.
+    pub fn set_value<T: 'static + PartialEq + Send>(
+        &self,
+        id: InputCellID<T>,
+        new_value: T,
+    ) -> bool {
+        self.lock().0.set_value(id, new_value)
+    }
+
+    /// See `Reactor::batch`.
+    ///
+    /// No other update can happen while the batch is in progress.
+    pub fn batch<F, R>(&self, updates: F) -> R
+    where
+        F: FnOnce(&mut Batch<'_, 'static>) -> R,
+    {
+        self.lock().0.batch(updates)
+    }
+
+    /// See `Reactor::add_callback`.
+    ///
+    /// Callbacks are called on whichever thread made the update which changed the cell.
+    pub fn add_callback<I, F>(&self, id: I, callback: F) -> Option<CallbackID>
+    where
+        I: TypedCellID,
+        F: 'static + FnMut(I::Value) + Send,
+    {
+        self.lock().0.add_callback(id, callback)
+    }
+
+    /// See `Reactor::add_change_callback`.
+    ///
+    /// Callbacks are called on whichever thread made the update which changed the cell.
+    pub fn add_change_callback<I, F>(&self, id: I, callback: F) -> Option<CallbackID>
+    where
+        I: TypedCellID,
+        F: 'static + FnMut(I::Value, I::Value) + Send,
+    {
+        self.lock().0.add_change_callback(id, callback)
+    }
+
+    /// See `Reactor::subscribe`.
+    ///
+    /// The receiver may be moved to any thread.
+    pub fn subscribe<I>(&self, id: I) -> Option<(CallbackID, Receiver<I::Value>)>
+    where
+        I: TypedCellID,
+        I::Value: Send,
+    {
+        self.lock().0.subscribe(id)
+    }
+
+    /// See `Reactor::remove_callback`.
+    pub fn remove_callback<I: TypedCellID>(
+        &self,
+        cell: I,
+        callback: CallbackID,
+    ) -> Result<(), RemoveCallbackError> {
+        self.lock().0.remove_callback(cell, callback)
+    }
+
+    /// See `Reactor::cells`.
+    pub fn cells(&self) -> Vec<(AnyCellID, CellKind)> {
+        self.lock().0.cells().collect()
+    }
+
+    /// See `Reactor::edges`.
+    pub fn edges(&self) -> Vec<(AnyCellID, AnyCellID)> {
+        self.lock().0.edges().collect()
+    }
+
+    /// See `Reactor::to_dot`.
+    pub fn to_dot(&self) -> String {
+        self.lock().0.to_dot()
+    }
+
+    /// See `Reactor::set_tracer`.
+    pub fn set_tracer<F>(&self, tracer: F)
+    where
+        F: 'static + FnMut(&Trace) + Send,
+    {
+        self.lock().0.set_tracer(tracer)
+    }
+
+    /// See `Reactor::clear_tracer`.
+    pub fn clear_tracer(&self) {
+        self.lock().0.clear_tracer()
+    }
+}
@@ -0,0 +1,37 @@
+use crate::{id::AnyCellID, CallbackID, Reactor};
+
+pub(crate) type Tracer<'a> = Box<dyn 'a + FnMut(&Trace)>;
+
+/// What happened while propagating a single update: a call to `set_value`, or a batch.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Trace {
+    /// The input cells which were set.
+    pub inputs: Vec<AnyCellID>,
+    /// The compute cells which were recomputed, in the order in which that happened.
+    pub recomputed: Vec<AnyCellID>,
+    /// The recomputed cells whose value changed.
+    pub changed: Vec<AnyCellID>,
+    /// The lazy compute cells which were marked out of date instead of being recomputed.
+    ///
+    /// They're recomputed when next read, which is not traced.
+    pub deferred: Vec<AnyCellID>,
+    /// The callbacks which were called, in the order in which that happened.
+    pub callbacks: Vec<CallbackID>,
+}
+
+impl<'a> Reactor<'a> {
+    // Sets a hook to be called with a trace of each update once it has been propagated.
+    //
+    // Replaces any hook which was previously set.
+    pub fn set_tracer<F>(&mut self, tracer: F)
+    where
+        F: 'a + FnMut(&Trace),
+    {
+        self.tracer = Some(Box::new(tracer));
+    }
+
+    // Removes the tracing hook, if any.
+    pub fn clear_tracer(&mut self) {
+        self.tracer = None;
+    }
+}
@@ -0,0 +1,26 @@
+use std::any::Any;
+
+/// A cell's value, with its type erased so that cells of different types can share a reactor.
+///
+/// This is public only so that it may appear in `Dependencies`; it's not nameable outside
+/// the crate.
+pub trait Value: Any {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    /// Whether `other` is of the same type and equal to this value.
+    fn eq_value(&self, other: &dyn Value) -> bool;
+}
+
+impl<T: Any + PartialEq> Value for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn eq_value(&self, other: &dyn Value) -> bool {
+        other.as_any().downcast_ref::<T>() == Some(self)
+    }
+}
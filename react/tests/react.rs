@@ -51,7 +51,7 @@ fn error_creating_compute_cell_if_input_doesnt_exist() {
     let input = dummy_reactor.create_input(1);
     assert_eq!(
         Reactor::new().create_compute(&[CellID::Input(input)], |_| 0),
-        Err(CellID::Input(input))
+        Err(CellID::Input(input).into())
     );
 }
 
@@ -64,7 +64,7 @@ fn do_not_break_cell_if_creating_compute_cell_with_valid_and_invalid_input() {
     let input = reactor.create_input(1);
     assert_eq!(
         reactor.create_compute(&[CellID::Input(input), CellID::Input(dummy_cell)], |_| 0),
-        Err(CellID::Input(dummy_cell))
+        Err(CellID::Input(dummy_cell).into())
     );
     assert!(reactor.set_value(input, 5));
     assert_eq!(reactor.value(CellID::Input(input)), Some(5));
@@ -440,3 +440,601 @@ fn test_adder_with_boolean_values() {
         );
     }
 }
+
+#[test]
+fn cells_can_hold_non_copy_values() {
+    let mut reactor = Reactor::new();
+    let first = reactor.create_input(String::from("hello"));
+    let second = reactor.create_input(String::from("world"));
+    let output = reactor
+        .create_compute(&[CellID::Input(first), CellID::Input(second)], |v| {
+            format!("{}, {}", v[0], v[1])
+        })
+        .unwrap();
+    assert_eq!(
+        reactor.value(CellID::Compute(output)),
+        Some("hello, world".to_string())
+    );
+    assert!(reactor.set_value(second, "there".to_string()));
+    assert_eq!(
+        reactor.value(CellID::Compute(output)),
+        Some("hello, there".to_string())
+    );
+}
+
+#[test]
+fn callbacks_receive_non_copy_values() {
+    let values = std::cell::RefCell::new(Vec::new());
+    {
+        let mut reactor = Reactor::new();
+        let input = reactor.create_input(vec![1.0, 2.0]);
+        let output = reactor
+            .create_compute(&[CellID::Input(input)], |v| {
+                v[0].iter().map(|x| x * 2.0).collect::<Vec<f64>>()
+            })
+            .unwrap();
+        assert!(reactor
+            .add_callback(output, |v| values.borrow_mut().push(v))
+            .is_some());
+        assert!(reactor.set_value(input, vec![3.0]));
+        assert!(reactor.set_value(input, vec![3.0]));
+    }
+    assert_eq!(values.into_inner(), vec![vec![6.0]]);
+}
+
+#[test]
+fn compute_cells_can_derive_values_of_another_type() {
+    let mut reactor = Reactor::new();
+    let width = reactor.create_input(2.0_f64);
+    let height = reactor.create_input(3.0_f64);
+    let is_square = reactor
+        .create_compute((width, height), |(w, h)| w == h)
+        .unwrap();
+    assert_eq!(reactor.value(is_square), Some(false));
+    assert!(reactor.set_value(height, 2.0));
+    assert_eq!(reactor.value(is_square), Some(true));
+}
+
+#[test]
+fn tuple_dependencies_may_mix_types_and_kinds() {
+    let values = std::cell::RefCell::new(Vec::new());
+    let mut reactor = Reactor::new();
+    let name = reactor.create_input(String::from("x"));
+    let count = reactor.create_input(2_usize);
+    let repeated = reactor
+        .create_compute((name, count), |(name, count)| name.repeat(count))
+        .unwrap();
+    let label = reactor
+        .create_compute((CellID::Compute(repeated), count), |(s, n)| {
+            format!("{} ({})", s, n)
+        })
+        .unwrap();
+    assert!(reactor
+        .add_callback(label, |v| values.borrow_mut().push(v))
+        .is_some());
+    assert!(reactor.set_value(count, 3));
+    assert_eq!(reactor.value(label), Some("xxx (3)".to_string()));
+    drop(reactor);
+    assert_eq!(values.into_inner(), vec!["xxx (3)".to_string()]);
+}
+
+#[test]
+fn cells_from_another_reactor_must_match_in_type() {
+    let mut dummy_reactor = Reactor::new();
+    let _ = dummy_reactor.create_input(1);
+    let dummy_cell = dummy_reactor.create_input(2);
+    let mut reactor = Reactor::new();
+    let _ = reactor.create_input(false);
+    let input = reactor.create_input("not a number");
+    assert_eq!(reactor.value(dummy_cell), None);
+    assert!(!reactor.set_value(dummy_cell, 3));
+    assert_eq!(
+        reactor.create_compute((input, dummy_cell), |_| 0),
+        Err(dummy_cell.into())
+    );
+}
+
+#[test]
+fn batched_updates_call_callbacks_once_with_the_final_value() {
+    let cb = CallbackRecorder::new();
+    let mut reactor = Reactor::new();
+    let a = reactor.create_input(1);
+    let b = reactor.create_input(2);
+    let c = reactor.create_input(3);
+    let sum = reactor
+        .create_compute(&[CellID::Input(a), CellID::Input(b)], |v| v[0] + v[1])
+        .unwrap();
+    let output = reactor
+        .create_compute(&[CellID::Compute(sum), CellID::Input(c)], |v| v[0] * v[1])
+        .unwrap();
+    assert!(reactor
+        .add_callback(output, |v| cb.callback_called(v))
+        .is_some());
+    assert!(reactor.batch(|batch| {
+        batch.set_value(a, 10) && batch.set_value(b, 20) && batch.set_value(c, 30)
+    }));
+    cb.expect_to_have_been_called_with(900);
+    assert_eq!(reactor.value(output), Some(900));
+}
+
+#[test]
+fn batched_updates_which_cancel_out_do_not_call_callbacks() {
+    let cb = CallbackRecorder::new();
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let output = reactor
+        .create_compute(&[CellID::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    assert!(reactor
+        .add_callback(output, |v| cb.callback_called(v))
+        .is_some());
+    reactor.batch(|batch| {
+        assert!(batch.set_value(input, 5));
+        assert!(batch.set_value(input, 1));
+    });
+    cb.expect_not_to_have_been_called();
+}
+
+#[test]
+fn error_setting_a_nonexistent_input_cell_in_a_batch() {
+    let mut dummy_reactor = Reactor::new();
+    let input = dummy_reactor.create_input(1);
+    assert!(!Reactor::new().batch(|batch| batch.set_value(input, 0)));
+}
+
+#[test]
+fn cells_without_dependents_can_be_removed() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let output = reactor
+        .create_compute(&[CellID::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    assert!(reactor.remove_cell(output).is_ok());
+    assert_eq!(reactor.value(output), None);
+    assert!(reactor.remove_cell(input).is_ok());
+    assert_eq!(reactor.value(input), None);
+    assert!(!reactor.set_value(input, 2));
+}
+
+#[test]
+fn error_removing_a_cell_with_dependents() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let output = reactor
+        .create_compute(&[CellID::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    assert_eq!(
+        reactor.remove_cell(input),
+        Err(RemoveCellError::HasDependents)
+    );
+    assert!(reactor.set_value(input, 2));
+    assert_eq!(reactor.value(output), Some(3));
+}
+
+#[test]
+fn error_removing_a_nonexistent_cell() {
+    let mut dummy_reactor = Reactor::new();
+    let input = dummy_reactor.create_input(1);
+    assert_eq!(
+        Reactor::new().remove_cell(input),
+        Err(RemoveCellError::NonexistentCell)
+    );
+    assert!(dummy_reactor.remove_cell(input).is_ok());
+    assert_eq!(
+        dummy_reactor.remove_cell(input),
+        Err(RemoveCellError::NonexistentCell)
+    );
+}
+
+#[test]
+fn removing_a_cell_cascades_to_its_dependents() {
+    let cb = CallbackRecorder::new();
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let other = reactor.create_input(10);
+    let plus_one = reactor
+        .create_compute(&[CellID::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    let sum = reactor
+        .create_compute(&[CellID::Compute(plus_one), CellID::Input(other)], |v| {
+            v[0] + v[1]
+        })
+        .unwrap();
+    let times_two = reactor
+        .create_compute(&[CellID::Input(other)], |v| v[0] * 2)
+        .unwrap();
+    assert!(reactor
+        .add_callback(sum, |v| cb.callback_called(v))
+        .is_some());
+
+    assert_eq!(
+        reactor.remove_cell_cascading(plus_one),
+        Ok(vec![plus_one.into(), sum.into()])
+    );
+    assert_eq!(reactor.value(sum), None);
+    assert!(reactor.set_value(other, 20));
+    cb.expect_not_to_have_been_called();
+    assert_eq!(reactor.value(times_two), Some(40));
+    // `other` no longer has `sum` as a dependent
+    assert!(reactor.remove_cell(times_two).is_ok());
+    assert!(reactor.remove_cell(other).is_ok());
+}
+
+#[test]
+fn ids_of_removed_cells_are_not_reused() {
+    let mut reactor = Reactor::new();
+    let first = reactor.create_input(1);
+    assert!(reactor.remove_cell(first).is_ok());
+    let second = reactor.create_input(2);
+    assert_ne!(first, second);
+    assert_eq!(reactor.value(first), None);
+    assert_eq!(reactor.value(second), Some(2));
+}
+
+#[test]
+fn lazy_compute_cells_only_compute_when_read() {
+    let calls = std::rc::Rc::new(std::cell::Cell::new(0));
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let counter = calls.clone();
+    let output = reactor
+        .create_lazy_compute(&[CellID::Input(input)], move |v| {
+            counter.set(counter.get() + 1);
+            v[0] * 2
+        })
+        .unwrap();
+    assert_eq!(calls.get(), 0);
+    assert!(reactor.set_value(input, 2));
+    assert!(reactor.set_value(input, 3));
+    assert_eq!(calls.get(), 0);
+    assert_eq!(reactor.value(output), Some(6));
+    assert_eq!(reactor.value(output), Some(6));
+    assert_eq!(calls.get(), 1);
+}
+
+#[test]
+fn compute_cells_can_depend_on_lazy_compute_cells() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let lazy = reactor
+        .create_lazy_compute(&[CellID::Input(input)], |v| v[0] * 2)
+        .unwrap();
+    let output = reactor
+        .create_compute(&[CellID::Compute(lazy)], |v| v[0] + 1)
+        .unwrap();
+    assert_eq!(reactor.value(output), Some(3));
+    assert!(reactor.set_value(input, 5));
+    assert_eq!(reactor.value(output), Some(11));
+    assert_eq!(reactor.value(lazy), Some(10));
+}
+
+#[test]
+fn lazy_compute_cells_fire_callbacks_on_change() {
+    let cb = CallbackRecorder::new();
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let output = reactor
+        .create_lazy_compute(&[CellID::Input(input)], |v| v[0] < 3)
+        .unwrap();
+    // changes made before the callback was added are not reported
+    assert!(reactor.set_value(input, 4));
+    assert!(reactor
+        .add_callback(output, |v| cb.callback_called(if v { 1 } else { 0 }))
+        .is_some());
+    assert!(reactor.set_value(input, 5));
+    cb.expect_not_to_have_been_called();
+    assert!(reactor.set_value(input, 2));
+    cb.expect_to_have_been_called_with(1);
+}
+
+#[test]
+fn sync_reactor_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SyncReactor>();
+}
+
+#[test]
+fn sync_reactor_accepts_updates_from_several_threads() {
+    let reactor = std::sync::Arc::new(SyncReactor::new());
+    let inputs: Vec<_> = (0..4).map(|_| reactor.create_input(0)).collect();
+    let total = reactor
+        .create_compute(inputs.as_slice(), |v| v.iter().sum::<i32>())
+        .unwrap();
+    let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = calls.clone();
+    assert!(reactor
+        .add_callback(total, move |_| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        })
+        .is_some());
+
+    let handles: Vec<_> = inputs
+        .iter()
+        .map(|&input| {
+            let reactor = reactor.clone();
+            std::thread::spawn(move || {
+                for value in 1..=10 {
+                    assert!(reactor.set_value(input, value));
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(reactor.value(total), Some(40));
+    assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 40);
+}
+
+#[test]
+fn sync_reactor_computations_need_not_be_sync() {
+    let reactor = SyncReactor::new();
+    let input = reactor.create_input(1);
+    let calls = std::cell::Cell::new(0);
+    let output = reactor
+        .create_compute((input,), move |(x,)| {
+            calls.set(calls.get() + 1);
+            x * calls.get()
+        })
+        .unwrap();
+    assert!(reactor.set_value(input, 2));
+    assert_eq!(reactor.value(output), Some(4));
+}
+
+#[test]
+#[should_panic(expected = "used the SyncReactor running it")]
+fn sync_reactor_panics_when_a_callback_reenters_it() {
+    let reactor = std::sync::Arc::new(SyncReactor::new());
+    let input = reactor.create_input(1);
+    let inner = std::sync::Arc::downgrade(&reactor);
+    assert!(reactor
+        .add_callback(input, move |_| {
+            if let Some(reactor) = inner.upgrade() {
+                reactor.value(input);
+            }
+        })
+        .is_some());
+    reactor.set_value(input, 2);
+}
+
+#[test]
+fn subscriptions_receive_changed_values() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let output = reactor
+        .create_compute(&[CellID::Input(input)], |v| v[0] / 2)
+        .unwrap();
+    let (callback, receiver) = reactor.subscribe(output).unwrap();
+    assert!(reactor.set_value(input, 2));
+    assert!(reactor.set_value(input, 3));
+    assert!(reactor.set_value(input, 4));
+    assert_eq!(receiver.try_iter().collect::<Vec<_>>(), vec![1, 2]);
+    assert!(reactor.remove_callback(output, callback).is_ok());
+    assert!(reactor.set_value(input, 6));
+    assert!(receiver.try_recv().is_err());
+}
+
+#[test]
+fn sync_reactor_subscriptions_can_be_received_on_another_thread() {
+    let reactor = SyncReactor::new();
+    let input = reactor.create_input(String::new());
+    let output = reactor.create_compute((input,), |(s,)| s.len()).unwrap();
+    let (_, receiver) = reactor.subscribe(output).unwrap();
+    let handle = std::thread::spawn(move || receiver.iter().take(2).collect::<Vec<_>>());
+    assert!(reactor.set_value(input, "ab".to_string()));
+    assert!(reactor.set_value(input, "cd".to_string()));
+    assert!(reactor.set_value(input, "abc".to_string()));
+    assert_eq!(handle.join().unwrap(), vec![2, 3]);
+}
+
+#[test]
+fn cells_and_edges_can_be_enumerated() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let removed = reactor.create_input(2);
+    let plus_one = reactor
+        .create_compute(&[CellID::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    let sum = reactor
+        .create_lazy_compute(&[CellID::Input(input), CellID::Compute(plus_one)], |v| {
+            v[0] + v[1]
+        })
+        .unwrap();
+    assert!(reactor.remove_cell(removed).is_ok());
+    assert_eq!(
+        reactor.cells().collect::<Vec<_>>(),
+        vec![
+            (input.into(), CellKind::Input),
+            (plus_one.into(), CellKind::Compute),
+            (sum.into(), CellKind::LazyCompute),
+        ]
+    );
+    assert_eq!(
+        reactor.edges().collect::<Vec<_>>(),
+        vec![
+            (input.into(), plus_one.into()),
+            (input.into(), sum.into()),
+            (plus_one.into(), sum.into()),
+        ]
+    );
+}
+
+#[test]
+fn dependency_graph_can_be_exported_as_dot() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let _ = reactor
+        .create_compute(&[CellID::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    assert_eq!(
+        reactor.to_dot(),
+        "digraph reactor {\n    cell0 [label=\"input 0\", shape=box];\n    cell1 [label=\"compute 1\", shape=ellipse];\n    cell0 -> cell1;\n}\n"
+    );
+}
+
+#[test]
+fn tracer_reports_each_update() {
+    let traces = std::cell::RefCell::new(Vec::new());
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let parity = reactor
+        .create_compute(&[CellID::Input(input)], |v| v[0] % 2)
+        .unwrap();
+    let lazy = reactor
+        .create_lazy_compute(&[CellID::Input(input)], |v| v[0] * 2)
+        .unwrap();
+    let callback = reactor.add_callback(parity, |_| ()).unwrap();
+    reactor.set_tracer(|trace: &Trace| traces.borrow_mut().push(trace.clone()));
+
+    assert!(reactor.set_value(input, 3));
+    assert!(reactor.set_value(input, 4));
+    reactor.clear_tracer();
+    assert!(reactor.set_value(input, 5));
+    drop(reactor);
+
+    assert_eq!(
+        traces.into_inner(),
+        vec![
+            Trace {
+                inputs: vec![input.into()],
+                recomputed: vec![parity.into()],
+                changed: vec![],
+                deferred: vec![lazy.into()],
+                callbacks: vec![],
+            },
+            Trace {
+                inputs: vec![input.into()],
+                recomputed: vec![parity.into()],
+                changed: vec![parity.into()],
+                deferred: vec![lazy.into()],
+                callbacks: vec![callback],
+            },
+        ]
+    );
+}
+
+#[test]
+fn fallible_compute_cells_expose_their_errors() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input("12".to_string());
+    let parsed = reactor
+        .create_fallible_compute((input,), |(s,)| s.parse::<i32>())
+        .unwrap();
+    assert_eq!(reactor.value(parsed), Some(Ok(12)));
+
+    assert!(reactor.set_value(input, "twelve".to_string()));
+    let error = reactor.value(parsed).unwrap().unwrap_err();
+    assert_eq!(error.cell(), parsed.into());
+    assert!(error.downcast_ref::<std::num::ParseIntError>().is_some());
+
+    assert!(reactor.set_value(input, "13".to_string()));
+    assert_eq!(reactor.value(parsed), Some(Ok(13)));
+}
+
+#[test]
+fn errors_propagate_without_calling_dependent_computations() {
+    let calls = std::rc::Rc::new(std::cell::Cell::new(0));
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input("2".to_string());
+    let parsed = reactor
+        .create_fallible_compute((input,), |(s,)| s.parse::<i32>())
+        .unwrap();
+    let counter = calls.clone();
+    let doubled = reactor
+        .create_fallible_compute((parsed,), move |(v,)| {
+            counter.set(counter.get() + 1);
+            Ok::<_, std::num::ParseIntError>(v * 2)
+        })
+        .unwrap();
+    assert_eq!(reactor.value(doubled), Some(Ok(4)));
+    assert_eq!(calls.get(), 1);
+
+    assert!(reactor.set_value(input, "two".to_string()));
+    assert_eq!(calls.get(), 1);
+    let error = reactor.value(doubled).unwrap().unwrap_err();
+    assert_eq!(error.cell(), parsed.into());
+
+    assert!(reactor.set_value(input, "3".to_string()));
+    assert_eq!(calls.get(), 2);
+    assert_eq!(reactor.value(doubled), Some(Ok(6)));
+}
+
+#[test]
+fn callbacks_are_called_when_a_fallible_cell_enters_or_leaves_error() {
+    let cb = CallbackRecorder::new();
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let reciprocal = reactor
+        .create_fallible_compute((input,), |(v,)| {
+            100i32.checked_div(v).ok_or(std::fmt::Error)
+        })
+        .unwrap();
+    assert!(reactor
+        .add_callback(reciprocal, |v: Result<i32, CellError>| cb
+            .callback_called(v.unwrap_or(-1)))
+        .is_some());
+
+    assert!(reactor.set_value(input, 0));
+    cb.expect_to_have_been_called_with(-1);
+    assert!(reactor.set_value(input, 4));
+    cb.expect_to_have_been_called_with(25);
+}
+
+#[test]
+fn callbacks_can_be_added_to_input_cells() {
+    let cb = CallbackRecorder::new();
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let callback = reactor
+        .add_callback(input, |v| cb.callback_called(v))
+        .unwrap();
+
+    assert!(reactor.set_value(input, 2));
+    cb.expect_to_have_been_called_with(2);
+    assert!(reactor.set_value(input, 2));
+    cb.expect_not_to_have_been_called();
+
+    assert_eq!(reactor.remove_callback(input, callback), Ok(()));
+    assert!(reactor.set_value(input, 3));
+    cb.expect_not_to_have_been_called();
+}
+
+#[test]
+fn change_callbacks_receive_the_previous_value() {
+    let changes = std::cell::RefCell::new(Vec::new());
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let output = reactor.create_compute((input,), |(v,)| v * 10).unwrap();
+    assert!(reactor
+        .add_change_callback(input, |old, new| changes.borrow_mut().push((old, new)))
+        .is_some());
+    assert!(reactor
+        .add_change_callback(output, |old, new| changes.borrow_mut().push((old, new)))
+        .is_some());
+
+    assert!(reactor.set_value(input, 2));
+    drop(reactor);
+    assert_eq!(changes.into_inner(), vec![(1, 2), (10, 20)]);
+}
+
+#[test]
+fn change_callbacks_in_a_batch_receive_the_value_from_before_the_batch() {
+    let changes = std::cell::RefCell::new(Vec::new());
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    assert!(reactor
+        .add_change_callback(input, |old, new| changes.borrow_mut().push((old, new)))
+        .is_some());
+
+    reactor.batch(|batch| {
+        assert!(batch.set_value(input, 2));
+        assert!(batch.set_value(input, 3));
+    });
+    reactor.batch(|batch| {
+        assert!(batch.set_value(input, 4));
+        assert!(batch.set_value(input, 3));
+    });
+    drop(reactor);
+    assert_eq!(changes.into_inner(), vec![(1, 3)]);
+}